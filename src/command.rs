@@ -0,0 +1,244 @@
+use std::fmt::{self, Display};
+use std::time::Duration;
+
+use crate::{MinecraftVersion, TextComponent};
+
+// Weather durations became a time argument in 1.20.5, a plain number now means ticks instead of seconds.
+const WEATHER_TICKS_SINCE: MinecraftVersion = MinecraftVersion::new(1, 20, 5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Weather {
+    Clear,
+    Rain,
+    Thunder,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WhitelistAction {
+    On,
+    Off,
+    Add(String),
+    Remove(String),
+    List,
+    Reload,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TeleportDestination {
+    Target(String),
+    Position { x: f64, y: f64, z: f64 },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ServerCommand {
+    Say(String),
+    Tellraw { targets: String, message: TextComponent },
+    Kick { player: String, reason: Option<String> },
+    Ban { player: String, reason: Option<String> },
+    BanIp { target: String, reason: Option<String> },
+    Pardon(String),
+    PardonIp(String),
+    Op(String),
+    Deop(String),
+    Whitelist(WhitelistAction),
+    Gamerule { rule: String, value: Option<String> },
+    TimeSet(u32),
+    TimeAdd(u32),
+    Weather { weather: Weather, duration: Option<Duration> },
+    SaveAll { flush: bool },
+    SaveOff,
+    SaveOn,
+    Teleport { target: String, destination: TeleportDestination },
+    Give { target: String, item: String, count: u32 },
+    Stop,
+}
+
+impl ServerCommand {
+    pub fn say<T: Into<String>>(message: T) -> Self {
+        ServerCommand::Say(message.into())
+    }
+
+    pub fn tellraw<T: Into<String>>(targets: T, message: TextComponent) -> Self {
+        ServerCommand::Tellraw {
+            targets: targets.into(),
+            message,
+        }
+    }
+
+    pub fn kick<T: Into<String>>(player: T) -> Self {
+        ServerCommand::Kick {
+            player: player.into(),
+            reason: None,
+        }
+    }
+
+    pub fn ban<T: Into<String>>(player: T) -> Self {
+        ServerCommand::Ban {
+            player: player.into(),
+            reason: None,
+        }
+    }
+
+    pub fn ban_ip<T: Into<String>>(target: T) -> Self {
+        ServerCommand::BanIp {
+            target: target.into(),
+            reason: None,
+        }
+    }
+
+    pub fn pardon<T: Into<String>>(player: T) -> Self {
+        ServerCommand::Pardon(player.into())
+    }
+
+    pub fn pardon_ip<T: Into<String>>(address: T) -> Self {
+        ServerCommand::PardonIp(address.into())
+    }
+
+    pub fn op<T: Into<String>>(player: T) -> Self {
+        ServerCommand::Op(player.into())
+    }
+
+    pub fn deop<T: Into<String>>(player: T) -> Self {
+        ServerCommand::Deop(player.into())
+    }
+
+    pub fn whitelist(action: WhitelistAction) -> Self {
+        ServerCommand::Whitelist(action)
+    }
+
+    pub fn gamerule<T: Into<String>, V: ToString>(rule: T, value: V) -> Self {
+        ServerCommand::Gamerule {
+            rule: rule.into(),
+            value: Some(value.to_string()),
+        }
+    }
+
+    pub fn weather(weather: Weather, duration: Option<Duration>) -> Self {
+        ServerCommand::Weather { weather, duration }
+    }
+
+    pub fn teleport<T: Into<String>>(target: T, destination: TeleportDestination) -> Self {
+        ServerCommand::Teleport {
+            target: target.into(),
+            destination,
+        }
+    }
+
+    pub fn give<T: Into<String>, I: Into<String>>(target: T, item: I, count: u32) -> Self {
+        ServerCommand::Give {
+            target: target.into(),
+            item: item.into(),
+            count,
+        }
+    }
+
+    pub fn reason<T: Into<String>>(mut self, reason: T) -> Self {
+        if let ServerCommand::Kick { reason: slot, .. }
+        | ServerCommand::Ban { reason: slot, .. }
+        | ServerCommand::BanIp { reason: slot, .. } = &mut self
+        {
+            *slot = Some(reason.into());
+        }
+        self
+    }
+
+    // Without a version the syntax of the latest release is used.
+    pub fn to_command(&self, version: Option<&MinecraftVersion>) -> String {
+        let with_reason = |command: &str, target: &str, reason: &Option<String>| match reason {
+            Some(reason) => format!("{} {} {}", command, target, single_line(reason)),
+            None => format!("{} {}", command, target),
+        };
+
+        match self {
+            ServerCommand::Say(message) => format!("say {}", single_line(message)),
+            ServerCommand::Tellraw { targets, message } => format!("tellraw {} {}", targets, message.to_json()),
+            ServerCommand::Kick { player, reason } => with_reason("kick", player, reason),
+            ServerCommand::Ban { player, reason } => with_reason("ban", player, reason),
+            ServerCommand::BanIp { target, reason } => with_reason("ban-ip", target, reason),
+            ServerCommand::Pardon(player) => format!("pardon {}", player),
+            ServerCommand::PardonIp(address) => format!("pardon-ip {}", address),
+            ServerCommand::Op(player) => format!("op {}", player),
+            ServerCommand::Deop(player) => format!("deop {}", player),
+            ServerCommand::Whitelist(action) => match action {
+                WhitelistAction::On => "whitelist on".to_string(),
+                WhitelistAction::Off => "whitelist off".to_string(),
+                WhitelistAction::Add(player) => format!("whitelist add {}", player),
+                WhitelistAction::Remove(player) => format!("whitelist remove {}", player),
+                WhitelistAction::List => "whitelist list".to_string(),
+                WhitelistAction::Reload => "whitelist reload".to_string(),
+            },
+            ServerCommand::Gamerule { rule, value } => match value {
+                Some(value) => format!("gamerule {} {}", rule, single_line(value)),
+                None => format!("gamerule {}", rule),
+            },
+            ServerCommand::TimeSet(ticks) => format!("time set {}", ticks),
+            ServerCommand::TimeAdd(ticks) => format!("time add {}", ticks),
+            ServerCommand::Weather { weather, duration } => {
+                let weather = match weather {
+                    Weather::Clear => "clear",
+                    Weather::Rain => "rain",
+                    Weather::Thunder => "thunder",
+                };
+                match duration {
+                    Some(duration) if version.is_none_or(|version| *version >= WEATHER_TICKS_SINCE) => {
+                        format!("weather {} {}", weather, (duration.as_millis() / 50).max(1))
+                    }
+                    Some(duration) => format!("weather {} {}", weather, duration.as_secs().max(1)),
+                    None => format!("weather {}", weather),
+                }
+            }
+            ServerCommand::SaveAll { flush: true } => "save-all flush".to_string(),
+            ServerCommand::SaveAll { flush: false } => "save-all".to_string(),
+            ServerCommand::SaveOff => "save-off".to_string(),
+            ServerCommand::SaveOn => "save-on".to_string(),
+            ServerCommand::Teleport { target, destination } => match destination {
+                TeleportDestination::Target(destination) => format!("tp {} {}", target, destination),
+                TeleportDestination::Position { x, y, z } => {
+                    format!("tp {} {} {} {}", target, coordinate(*x), coordinate(*y), coordinate(*z))
+                }
+            },
+            ServerCommand::Give { target, item, count: 1 } => format!("give {} {}", target, item),
+            ServerCommand::Give { target, item, count } => format!("give {} {} {}", target, item, count),
+            ServerCommand::Stop => "stop".to_string(),
+        }
+    }
+}
+
+impl Display for ServerCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_command(None))
+    }
+}
+
+// Free text ends up on a single console line, a line break would start a second command.
+fn single_line(text: &str) -> String {
+    text.replace(['\r', '\n'], " ")
+}
+
+// Whole numbers must keep their decimal point, the game centers `100` on the block as 100.5.
+fn coordinate(value: f64) -> String {
+    if value.fract() == 0.0 && value.is_finite() {
+        format!("{:.1}", value)
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn weather_duration_is_in_ticks_since_1_20_5() {
+        let command = ServerCommand::weather(Weather::Rain, Some(Duration::from_secs(30)));
+        assert_eq!(command.to_command(None), "weather rain 600");
+        assert_eq!(command.to_command(Some(&MinecraftVersion::new(1, 20, 4))), "weather rain 30");
+    }
+
+    #[test]
+    fn weather_duration_is_at_least_one_unit() {
+        let command = ServerCommand::weather(Weather::Clear, Some(Duration::from_millis(10)));
+        assert_eq!(command.to_command(None), "weather clear 1");
+        assert_eq!(command.to_command(Some(&MinecraftVersion::new(1, 20, 4))), "weather clear 1");
+    }
+}
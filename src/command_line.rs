@@ -0,0 +1,65 @@
+use std::borrow::Cow;
+use std::fmt::{self, Display};
+use std::process::Command;
+
+pub(crate) const RUN_SCRIPT_MARKER: &str = "# Generated by mslc";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandLine {
+    pub program: String,
+    pub args: Vec<String>,
+    pub working_dir: String,
+    pub env: Vec<(String, String)>,
+}
+
+impl CommandLine {
+    pub fn to_command(&self) -> Command {
+        let mut command = Command::new(&self.program);
+        command
+            .args(&self.args)
+            .envs(self.env.iter().map(|(key, value)| (key, value)))
+            .current_dir(&self.working_dir);
+        command
+    }
+
+    pub fn to_shell(&self) -> String {
+        let mut line = format!("cd {} && ", shell_quote(&self.working_dir));
+        for (key, value) in &self.env {
+            line.push_str(&format!("{}={} ", key, shell_quote(value)));
+        }
+        line.push_str(&self.invocation());
+        line
+    }
+
+    pub fn to_run_script(&self) -> String {
+        let mut script = format!("#!/bin/sh\n{}\ncd \"$(dirname \"$0\")\" || exit 1\n", RUN_SCRIPT_MARKER);
+        for (key, value) in &self.env {
+            script.push_str(&format!("export {}={}\n", key, shell_quote(value)));
+        }
+        script.push_str(&format!("exec {} \"$@\"\n", self.invocation()));
+        script
+    }
+
+    fn invocation(&self) -> String {
+        std::iter::once(&self.program)
+            .chain(&self.args)
+            .map(|arg| shell_quote(arg))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+impl Display for CommandLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_shell())
+    }
+}
+
+fn shell_quote(arg: &str) -> Cow<'_, str> {
+    let safe = |c: char| c.is_ascii_alphanumeric() || "_@%+=:,./-".contains(c);
+    if !arg.is_empty() && arg.chars().all(safe) {
+        Cow::Borrowed(arg)
+    } else {
+        Cow::Owned(format!("'{}'", arg.replace('\'', "'\\''")))
+    }
+}
@@ -0,0 +1,51 @@
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::ServerProperties;
+
+const EULA_FILE: &str = "eula.txt";
+
+const EULA_HEADER: &str = "By changing the setting below to TRUE you are indicating your agreement to our EULA (https://aka.ms/MinecraftEULA).";
+
+pub(crate) fn is_accepted(server_path: &Path) -> bool {
+    ServerProperties::load(server_path.join(EULA_FILE))
+        .map(|eula| eula.get("eula").is_some_and(|value| value.trim().eq_ignore_ascii_case("true")))
+        .unwrap_or(false)
+}
+
+pub(crate) fn accept(server_path: &Path) -> Result<(), std::io::Error> {
+    let contents = format!("#{}\n#{}\neula=true\n", EULA_HEADER, java_date(SystemTime::now()));
+    std::fs::write(server_path.join(EULA_FILE), contents)
+}
+
+// Mirrors java.util.Date#toString, which is what the server itself writes below the header.
+fn java_date(time: SystemTime) -> String {
+    const DAYS: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+    const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+
+    let secs = time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let days = secs / 86400;
+    let (hour, minute, second) = (secs % 86400 / 3600, secs % 3600 / 60, secs % 60);
+
+    // Civil-from-days, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{} {} {:02} {:02}:{:02}:{:02} UTC {}",
+        DAYS[(days % 7) as usize],
+        MONTHS[(month - 1) as usize],
+        day,
+        hour,
+        minute,
+        second,
+        year
+    )
+}
@@ -0,0 +1,237 @@
+use std::fmt::{self, Display};
+use std::path::Path;
+
+use crate::jar::ServerJar;
+use crate::{LaunchTarget, MinecraftVersion, ServerProperties};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ServerKind {
+    Vanilla,
+    Spigot,
+    Paper,
+    Purpur,
+    Folia,
+    Fabric,
+    Quilt,
+    Forge,
+    NeoForge,
+    Unknown,
+}
+
+impl Display for ServerKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ServerKind::Vanilla => "Vanilla",
+            ServerKind::Spigot => "Spigot",
+            ServerKind::Paper => "Paper",
+            ServerKind::Purpur => "Purpur",
+            ServerKind::Folia => "Folia",
+            ServerKind::Fabric => "Fabric",
+            ServerKind::Quilt => "Quilt",
+            ServerKind::Forge => "Forge",
+            ServerKind::NeoForge => "NeoForge",
+            ServerKind::Unknown => "Unknown",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerFlavor {
+    pub kind: ServerKind,
+    pub minecraft_version: Option<String>,
+    pub loader_version: Option<String>,
+}
+
+impl ServerFlavor {
+    pub fn minecraft(&self) -> Option<MinecraftVersion> {
+        self.minecraft_version.as_deref()?.parse().ok()
+    }
+
+    pub fn detect<P: AsRef<Path>>(server_path: P, target: &LaunchTarget) -> ServerFlavor {
+        let server_path = server_path.as_ref();
+        let jar_flavor = target.jar().and_then(|jar| detect_jar(server_path, &server_path.join(jar)));
+        if let Some(flavor) = jar_flavor.as_ref()
+            && flavor.kind != ServerKind::Unknown
+        {
+            return flavor.clone();
+        }
+        detect_forge_libraries(server_path, target)
+            .or(jar_flavor)
+            .unwrap_or_else(|| ServerFlavor::new(ServerKind::Unknown, None, None))
+    }
+
+    fn new(kind: ServerKind, minecraft_version: Option<String>, loader_version: Option<String>) -> Self {
+        ServerFlavor {
+            kind,
+            minecraft_version,
+            loader_version,
+        }
+    }
+}
+
+impl Display for ServerFlavor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.kind)?;
+        if let Some(version) = &self.minecraft_version {
+            write!(f, " {}", version)?;
+        }
+        if let Some(loader) = &self.loader_version {
+            write!(f, " (loader {})", loader)?;
+        }
+        Ok(())
+    }
+}
+
+// Installer based layouts keep the loader under libraries/ and launch through run.sh or argfiles,
+// which name the exact version in use when several are installed side by side.
+fn detect_forge_libraries(server_path: &Path, target: &LaunchTarget) -> Option<ServerFlavor> {
+    let mut launch_scripts = target.args().join(" ");
+    for script in ["run.sh", "run.bat"] {
+        if let Ok(contents) = std::fs::read_to_string(server_path.join(script)) {
+            launch_scripts.push('\n');
+            launch_scripts.push_str(&contents);
+        }
+    }
+    let library_version = |group: &str| {
+        referenced_library_version(&launch_scripts, group).or_else(|| latest_library_version(server_path, group))
+    };
+
+    if let Some(version) = library_version("net/neoforged/neoforge") {
+        let minecraft = neoforge_minecraft_version(&version);
+        return Some(ServerFlavor::new(ServerKind::NeoForge, minecraft, Some(version)));
+    }
+    for (group, kind) in [
+        ("net/neoforged/forge", ServerKind::NeoForge),
+        ("net/minecraftforge/forge", ServerKind::Forge),
+    ] {
+        if let Some(version) = library_version(group) {
+            let (minecraft, loader) = match version.split_once('-') {
+                Some((minecraft, loader)) => (Some(minecraft.to_string()), loader.to_string()),
+                None => (None, version),
+            };
+            return Some(ServerFlavor::new(kind, minecraft, Some(loader)));
+        }
+    }
+    None
+}
+
+fn detect_jar(server_path: &Path, jar_path: &Path) -> Option<ServerFlavor> {
+    let mut jar = ServerJar::open(jar_path).ok()?;
+    let main_class = jar.main_class().unwrap_or_default();
+    let mut minecraft_version = jar
+        .version_json()
+        .and_then(|json| Some(json.get("id")?.as_str()?.to_string()));
+
+    if let Some(versions) = jar.read("META-INF/versions.list") {
+        let versions = String::from_utf8_lossy(&versions);
+        let id = versions.lines().find_map(|line| line.split('\t').nth(1))?.to_string();
+        let (name, version) = id.split_once('-').unwrap_or((id.as_str(), ""));
+        let kind = match name {
+            "purpur" => ServerKind::Purpur,
+            "folia" => ServerKind::Folia,
+            "paper" => ServerKind::Paper,
+            "spigot" => ServerKind::Spigot,
+            _ if main_class.starts_with("io.papermc.") => ServerKind::Paper,
+            _ => ServerKind::Vanilla,
+        };
+        if minecraft_version.is_none() && !version.is_empty() {
+            minecraft_version = Some(version.to_string());
+        }
+        return Some(ServerFlavor::new(kind, minecraft_version, None));
+    }
+
+    if let Some(install) = jar.read("install.properties") {
+        let install = ServerProperties::parse(&String::from_utf8_lossy(&install));
+        let kind = if main_class.starts_with("org.quiltmc.") {
+            ServerKind::Quilt
+        } else {
+            ServerKind::Fabric
+        };
+        let loader = install
+            .get("fabric-loader-version")
+            .or_else(|| install.get("quilt-loader-version"))
+            .map(str::to_string);
+        return Some(ServerFlavor::new(kind, install.get("game-version").map(str::to_string), loader));
+    }
+
+    let fabric = main_class.starts_with("net.fabricmc.") || jar.contains("fabric-server-launch.properties");
+    if fabric || main_class.starts_with("org.quiltmc.") {
+        let (kind, group) = if fabric {
+            (ServerKind::Fabric, "net/fabricmc/fabric-loader")
+        } else {
+            (ServerKind::Quilt, "org/quiltmc/quilt-loader")
+        };
+        // The launcher delegates to the vanilla jar named in fabric-server-launcher.properties.
+        let vanilla_jar = ServerProperties::load(server_path.join("fabric-server-launcher.properties"))
+            .ok()
+            .and_then(|launcher| launcher.get("serverJar").map(str::to_string))
+            .unwrap_or_else(|| "server.jar".to_string());
+        let minecraft_version = ServerJar::open(server_path.join(vanilla_jar))
+            .ok()
+            .and_then(|mut jar| Some(jar.version_json()?.get("id")?.as_str()?.to_string()));
+        return Some(ServerFlavor::new(kind, minecraft_version, latest_library_version(server_path, group)));
+    }
+
+    if main_class.starts_with("net.minecraftforge.") || main_class.starts_with("cpw.mods.") {
+        // Forge server jars are named forge-<minecraft>-<forge>[-universal|-shim].jar.
+        let file_name = jar_path.file_stem()?.to_string_lossy();
+        let versions = file_name
+            .strip_prefix("forge-")
+            .map(|rest| rest.trim_end_matches("-universal").trim_end_matches("-shim"));
+        let (minecraft, loader) = match versions.and_then(|versions| versions.split_once('-')) {
+            Some((minecraft, loader)) => (Some(minecraft.to_string()), Some(loader.to_string())),
+            None => (minecraft_version, None),
+        };
+        return Some(ServerFlavor::new(ServerKind::Forge, minecraft, loader));
+    }
+
+    if main_class.starts_with("org.bukkit.craftbukkit.") {
+        return Some(ServerFlavor::new(ServerKind::Spigot, minecraft_version, None));
+    }
+
+    if main_class.starts_with("net.minecraft.") {
+        return Some(ServerFlavor::new(ServerKind::Vanilla, minecraft_version, None));
+    }
+
+    Some(ServerFlavor::new(ServerKind::Unknown, minecraft_version, None))
+}
+
+fn referenced_library_version(text: &str, group: &str) -> Option<String> {
+    let prefix = format!("libraries/{}/", group);
+    let normalized = text.replace('\\', "/");
+    let start = normalized.find(&prefix)? + prefix.len();
+    let version = normalized[start..].split('/').next()?;
+    (!version.is_empty()).then(|| version.to_string())
+}
+
+fn latest_library_version(server_path: &Path, group: &str) -> Option<String> {
+    let entries = std::fs::read_dir(server_path.join("libraries").join(group)).ok()?;
+    entries
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            entry.file_type().ok()?.is_dir().then(|| entry.file_name().to_string_lossy().into_owned())
+        })
+        .max_by(|a, b| compare_versions(a, b))
+}
+
+// NeoForge versions encode the Minecraft version: 20.4.237 is for 1.20.4, 21.0.10 for 1.21.
+fn neoforge_minecraft_version(version: &str) -> Option<String> {
+    let mut parts = version.split('.');
+    let major: u32 = parts.next()?.parse().ok()?;
+    let minor: u32 = parts.next()?.parse().ok()?;
+    Some(match (major, minor) {
+        (major, 0) if major <= 21 => format!("1.{}", major),
+        (major, minor) if major <= 21 => format!("1.{}.{}", major, minor),
+        (major, minor) => format!("{}.{}", major, minor),
+    })
+}
+
+fn compare_versions(a: &str, b: &str) -> std::cmp::Ordering {
+    let key = |version: &str| -> Vec<u64> {
+        version
+            .split(|c: char| !c.is_ascii_digit())
+            .filter_map(|part| part.parse().ok())
+            .collect()
+    };
+    key(a).cmp(&key(b))
+}
@@ -0,0 +1,404 @@
+use std::io::Write;
+use std::process::{Child, ChildStdin, ExitStatus};
+use std::sync::mpsc::Receiver;
+use std::sync::{Arc, Condvar, Mutex, PoisonError};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use crate::log::{LogParser, ServerEvent};
+use crate::output::{OutputHub, OutputLine, OutputStream};
+use crate::{MinecraftVersion, ServerCommand};
+
+pub struct ServerHandle {
+    child: Child,
+    stdin: Option<ChildStdin>,
+    output: Arc<OutputHub>,
+    captures_output: bool,
+    readers: Vec<JoinHandle<()>>,
+    stop_requested: bool,
+    started: Instant,
+    ready: Arc<(Mutex<Option<Duration>>, Condvar)>,
+    minecraft_version: Option<MinecraftVersion>,
+}
+
+impl ServerHandle {
+    pub(crate) fn new(
+        mut child: Child,
+        echo_output: bool,
+        output_tail_lines: usize,
+        minecraft_version: Option<MinecraftVersion>,
+    ) -> Self {
+        let stdin = child.stdin.take();
+        let output = Arc::new(OutputHub::new(echo_output, output_tail_lines));
+        // Remember readiness as it happens, the Done line may scroll out of the tail long before anyone asks.
+        let ready = Arc::new((Mutex::new(None), Condvar::new()));
+        let mut parser = LogParser::new();
+        let ready_signal = Arc::clone(&ready);
+        output.on_output(move |line| {
+            if let Some(ServerEvent::Ready { startup }) = parser.parse(&line.line) {
+                let (startup_time, ready) = &*ready_signal;
+                startup_time.lock().unwrap_or_else(PoisonError::into_inner).get_or_insert(startup);
+                ready.notify_all();
+            }
+        });
+        let mut readers = Vec::new();
+        if let Some(stdout) = child.stdout.take() {
+            readers.push(output.attach(stdout, OutputStream::Stdout));
+        }
+        if let Some(stderr) = child.stderr.take() {
+            readers.push(output.attach(stderr, OutputStream::Stderr));
+        }
+
+        ServerHandle {
+            child,
+            stdin,
+            output,
+            captures_output: !readers.is_empty(),
+            readers,
+            stop_requested: false,
+            started: Instant::now(),
+            ready,
+            minecraft_version,
+        }
+    }
+
+    pub fn pid(&self) -> u32 {
+        self.child.id()
+    }
+
+    pub fn try_wait(&mut self) -> Result<Option<ServerExitStatus>, std::io::Error> {
+        Ok(self.child.try_wait()?.map(ServerExitStatus::from))
+    }
+
+    pub fn wait(&mut self) -> Result<ServerExitStatus, std::io::Error> {
+        let status = self.child.wait()?;
+        for reader in self.readers.drain(..) {
+            let _ = reader.join();
+        }
+        Ok(status.into())
+    }
+
+    pub fn wait_with_report(&mut self) -> Result<ExitReport, std::io::Error> {
+        let status = self.wait()?;
+        Ok(ExitReport {
+            status,
+            runtime: self.started.elapsed(),
+            stop_requested: self.stop_requested,
+            output_tail: self.output.tail(),
+        })
+    }
+
+    pub fn kill(&mut self) -> Result<(), std::io::Error> {
+        self.child.kill()
+    }
+
+    pub fn stop_requested(&self) -> bool {
+        self.stop_requested
+    }
+
+    pub fn stop(&mut self, timeout: Duration) -> Result<StopOutcome, std::io::Error> {
+        self.stop_with(StopPolicy {
+            command_timeout: timeout,
+            ..StopPolicy::default()
+        })
+    }
+
+    pub fn stop_with(&mut self, policy: StopPolicy) -> Result<StopOutcome, std::io::Error> {
+        if let Some(status) = self.try_wait()? {
+            return Ok(StopOutcome::new(StopStage::AlreadyExited, status));
+        }
+        self.stop_requested = true;
+
+        match self.send_command("stop") {
+            Ok(()) => {
+                if let Some(status) = self.wait_timeout(policy.command_timeout)? {
+                    return Ok(StopOutcome::new(StopStage::Command, status));
+                }
+            }
+            // The child is already reaped here, it went down on its own before the command got through.
+            Err(SendCommandError::ServerExited(status)) => {
+                self.stop_requested = false;
+                return Ok(StopOutcome::new(StopStage::AlreadyExited, status));
+            }
+            Err(_) => {}
+        }
+
+        #[cfg(unix)]
+        if self.terminate()?
+            && let Some(status) = self.wait_timeout(policy.terminate_timeout)?
+        {
+            return Ok(StopOutcome::new(StopStage::Terminate, status));
+        }
+        // Exited right after the command timed out.
+        if let Some(status) = self.wait_timeout(Duration::ZERO)? {
+            return Ok(StopOutcome::new(StopStage::Command, status));
+        }
+
+        self.kill()?;
+        let status = self.wait()?;
+        Ok(StopOutcome::new(StopStage::Kill, status))
+    }
+
+    #[cfg(unix)]
+    fn terminate(&mut self) -> Result<bool, std::io::Error> {
+        // Once reaped the pid may belong to an unrelated process.
+        if self.child.try_wait()?.is_some() {
+            return Ok(false);
+        }
+        let pid = self.child.id() as libc::pid_t;
+        if unsafe { libc::kill(pid, libc::SIGTERM) } == 0 {
+            return Ok(true);
+        }
+        let e = std::io::Error::last_os_error();
+        if e.raw_os_error() == Some(libc::ESRCH) {
+            Ok(false)
+        } else {
+            Err(e)
+        }
+    }
+
+    fn wait_timeout(&mut self, timeout: Duration) -> Result<Option<ServerExitStatus>, std::io::Error> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if self.child.try_wait()?.is_some() {
+                return self.wait().map(Some);
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return Ok(None);
+            }
+            std::thread::sleep((deadline - now).min(Duration::from_millis(50)));
+        }
+    }
+
+    pub fn captures_output(&self) -> bool {
+        self.captures_output
+    }
+
+    pub fn on_output<F: FnMut(&OutputLine) + Send + 'static>(&self, callback: F) {
+        self.output.on_output(callback);
+    }
+
+    pub fn subscribe(&self) -> Receiver<OutputLine> {
+        self.output.subscribe()
+    }
+
+    pub fn output_tail(&self) -> Vec<OutputLine> {
+        self.output.tail()
+    }
+
+    pub fn wait_ready(&mut self, timeout: Duration) -> Result<Duration, WaitReadyError> {
+        if !self.captures_output {
+            return Err(WaitReadyError::OutputNotCaptured);
+        }
+
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(startup) = self.startup_time() {
+                return Ok(startup);
+            }
+            if self.child.try_wait()?.is_some() {
+                // Joining the readers flushes any output that was still in flight.
+                let status = self.wait()?;
+                if let Some(startup) = self.startup_time() {
+                    return Ok(startup);
+                }
+                return Err(WaitReadyError::Exited {
+                    status,
+                    tail: self.output.tail(),
+                });
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(WaitReadyError::TimedOut {
+                    timeout,
+                    tail: self.output.tail(),
+                });
+            }
+
+            let (startup_time, ready) = &*self.ready;
+            let startup = startup_time.lock().unwrap_or_else(PoisonError::into_inner);
+            if startup.is_none() {
+                let _ = ready.wait_timeout(startup, (deadline - now).min(Duration::from_millis(100)));
+            }
+        }
+    }
+
+    fn startup_time(&self) -> Option<Duration> {
+        *self.ready.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub fn on_event<F: FnMut(&ServerEvent) + Send + 'static>(&self, mut callback: F) {
+        let mut parser = LogParser::new();
+        self.output.on_output(move |line| {
+            if let Some(event) = parser.parse(&line.line) {
+                callback(&event);
+            }
+        });
+    }
+
+    pub fn send(&mut self, command: &ServerCommand) -> Result<(), SendCommandError> {
+        self.send_command(&command.to_command(self.minecraft_version.as_ref()))
+    }
+
+    pub fn send_command(&mut self, command: &str) -> Result<(), SendCommandError> {
+        if command.contains(['\n', '\r']) {
+            return Err(SendCommandError::InvalidCommand(command.to_string()));
+        }
+        if let Some(status) = self.try_wait()? {
+            self.stdin = None;
+            return Err(SendCommandError::ServerExited(status));
+        }
+        let stdin = self.stdin.as_mut().ok_or(SendCommandError::StdinNotPiped)?;
+        if command.trim() == "stop" {
+            self.stop_requested = true;
+        }
+
+        let result = stdin
+            .write_all(command.as_bytes())
+            .and_then(|_| stdin.write_all(b"\n"))
+            .and_then(|_| stdin.flush());
+        match result {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::BrokenPipe => {
+                self.stdin = None;
+                match self.wait() {
+                    Ok(status) => Err(SendCommandError::ServerExited(status)),
+                    Err(_) => Err(SendCommandError::Io(e)),
+                }
+            }
+            Err(e) => Err(SendCommandError::Io(e)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ExitReport {
+    pub status: ServerExitStatus,
+    pub runtime: Duration,
+    pub stop_requested: bool,
+    pub output_tail: Vec<OutputLine>,
+}
+
+impl ExitReport {
+    pub fn code(&self) -> Option<i32> {
+        self.status.code()
+    }
+
+    pub fn signal(&self) -> Option<i32> {
+        self.status.signal()
+    }
+
+    pub fn is_abnormal(&self) -> bool {
+        !self.stop_requested && !self.status.success()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StopPolicy {
+    pub command_timeout: Duration,
+    pub terminate_timeout: Duration,
+}
+
+impl Default for StopPolicy {
+    fn default() -> Self {
+        StopPolicy {
+            command_timeout: Duration::from_secs(60),
+            terminate_timeout: Duration::from_secs(15),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopStage {
+    AlreadyExited,
+    Command,
+    Terminate,
+    Kill,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StopOutcome {
+    pub stage: StopStage,
+    pub status: ServerExitStatus,
+}
+
+impl StopOutcome {
+    fn new(stage: StopStage, status: ServerExitStatus) -> Self {
+        StopOutcome { stage, status }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum WaitReadyError {
+    #[error("server output is not captured")]
+    OutputNotCaptured,
+    #[error("server exited with {status} before it was ready")]
+    Exited {
+        status: ServerExitStatus,
+        tail: Vec<OutputLine>,
+    },
+    #[error("server was not ready within {timeout:?}")]
+    TimedOut {
+        timeout: Duration,
+        tail: Vec<OutputLine>,
+    },
+    #[error("failed to wait for server: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SendCommandError {
+    #[error("server stdin is not piped")]
+    StdinNotPiped,
+    #[error("server has exited with {0}")]
+    ServerExited(ServerExitStatus),
+    #[error("command must be a single line: {0:?}")]
+    InvalidCommand(String),
+    #[error("failed to write command: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ServerExitStatus {
+    code: Option<i32>,
+    signal: Option<i32>,
+}
+
+impl ServerExitStatus {
+    pub fn success(&self) -> bool {
+        self.code == Some(0)
+    }
+
+    pub fn code(&self) -> Option<i32> {
+        self.code
+    }
+
+    pub fn signal(&self) -> Option<i32> {
+        self.signal
+    }
+}
+
+impl From<ExitStatus> for ServerExitStatus {
+    fn from(status: ExitStatus) -> Self {
+        #[cfg(unix)]
+        let signal = std::os::unix::process::ExitStatusExt::signal(&status);
+        #[cfg(not(unix))]
+        let signal = None;
+
+        ServerExitStatus {
+            code: status.code(),
+            signal,
+        }
+    }
+}
+
+impl std::fmt::Display for ServerExitStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.code, self.signal) {
+            (Some(code), _) => write!(f, "exit code {}", code),
+            (None, Some(signal)) => write!(f, "signal {}", signal),
+            (None, None) => write!(f, "unknown exit status"),
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use std::fmt::{self, Display};
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use zip::ZipArchive;
+
+use crate::{JavaVersion, MinecraftVersion};
+
+const LEGACY_FORGE_MAIN_CLASSES: &[&str] = &[
+    "net.minecraftforge.fml.relauncher.ServerLaunchWrapper",
+    "cpw.mods.fml.relauncher.ServerLaunchWrapper",
+];
+
+// Launchers are compiled for an old Java release regardless of the game they start.
+const LAUNCHER_MAIN_CLASS_PREFIXES: &[&str] = &[
+    "net.fabricmc.",
+    "org.quiltmc.",
+    "io.papermc.paperclip.",
+    "com.destroystokyo.paperclip.",
+    "net.minecraft.bundler.",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JavaRequirement {
+    pub min: u32,
+    pub max: Option<u32>,
+}
+
+impl JavaRequirement {
+    pub fn is_satisfied_by(&self, version: &JavaVersion) -> bool {
+        version.major >= self.min && self.max.is_none_or(|max| version.major <= max)
+    }
+
+    pub fn from_minecraft_version(version: &MinecraftVersion) -> Self {
+        JavaRequirement {
+            min: version.required_java(),
+            max: None,
+        }
+    }
+
+    pub fn from_jar<P: AsRef<Path>>(path: P) -> Option<Self> {
+        let mut jar = ServerJar::open(path).ok()?;
+        Self::from_version_json(&mut jar).or_else(|| Self::from_main_class(&mut jar))
+    }
+
+    pub(crate) fn from_version_json(jar: &mut ServerJar) -> Option<Self> {
+        if let Some(version) = jar.version_json() {
+            if let Some(min) = version.get("java_version").and_then(|v| v.as_u64()) {
+                return Some(JavaRequirement { min: min as u32, max: None });
+            }
+            if let Some(id) = version.get("id").and_then(|id| id.as_str())
+                && let Ok(version) = id.parse()
+            {
+                return Some(Self::from_minecraft_version(&version));
+            }
+        }
+
+        let main_class = jar.main_class()?;
+        if LEGACY_FORGE_MAIN_CLASSES.contains(&main_class.as_str()) {
+            return Some(JavaRequirement { min: 8, max: Some(8) });
+        }
+        None
+    }
+
+    // Only a guess: the bytecode level of the entry point, not necessarily of the game itself.
+    pub(crate) fn from_main_class(jar: &mut ServerJar) -> Option<Self> {
+        let main_class = jar.main_class()?;
+        if LAUNCHER_MAIN_CLASS_PREFIXES.iter().any(|prefix| main_class.starts_with(prefix)) {
+            return None;
+        }
+        let min = jar.class_java_version(&main_class)?;
+        Some(JavaRequirement { min, max: None })
+    }
+}
+
+impl Display for JavaRequirement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.max {
+            Some(max) if max == self.min => write!(f, "Java {}", self.min),
+            Some(max) => write!(f, "Java {} to {}", self.min, max),
+            None => write!(f, "Java {} or newer", self.min),
+        }
+    }
+}
+
+pub(crate) struct ServerJar {
+    archive: ZipArchive<File>,
+}
+
+impl ServerJar {
+    pub(crate) fn open<P: AsRef<Path>>(path: P) -> Result<Self, zip::result::ZipError> {
+        let archive = ZipArchive::new(File::open(path)?)?;
+        Ok(ServerJar { archive })
+    }
+
+    pub(crate) fn read(&mut self, name: &str) -> Option<Vec<u8>> {
+        let mut entry = self.archive.by_name(name).ok()?;
+        let mut bytes = Vec::new();
+        entry.read_to_end(&mut bytes).ok()?;
+        Some(bytes)
+    }
+
+    pub(crate) fn version_json(&mut self) -> Option<serde_json::Value> {
+        serde_json::from_slice(&self.read("version.json")?).ok()
+    }
+
+    pub(crate) fn manifest_attribute(&mut self, name: &str) -> Option<String> {
+        let manifest = self.read("META-INF/MANIFEST.MF")?;
+        let manifest = String::from_utf8_lossy(&manifest);
+
+        // Manifest lines are wrapped at 72 bytes, continuation lines start with a single space.
+        let mut value: Option<String> = None;
+        for line in manifest.lines() {
+            if let Some(value) = value.as_mut() {
+                match line.strip_prefix(' ') {
+                    Some(continuation) => value.push_str(continuation),
+                    None => break,
+                }
+            } else if let Some((key, rest)) = line.split_once(':')
+                && key.eq_ignore_ascii_case(name)
+            {
+                value = Some(rest.trim_start().to_string());
+            }
+        }
+        value.map(|value| value.trim_end().to_string()).filter(|value| !value.is_empty())
+    }
+
+    pub(crate) fn main_class(&mut self) -> Option<String> {
+        self.manifest_attribute("Main-Class")
+    }
+
+    pub(crate) fn contains(&self, name: &str) -> bool {
+        self.archive.index_for_name(name).is_some()
+    }
+
+    pub(crate) fn is_executable(&mut self) -> bool {
+        self.main_class().is_some()
+            || self.contains("META-INF/main-class")
+            || self.contains("net/minecraft/bundler/Main.class")
+    }
+
+    // Class files store their format version at bytes 6..8; major 52 is Java 8, 61 is Java 17.
+    pub(crate) fn class_java_version(&mut self, class: &str) -> Option<u32> {
+        let bytes = self.read(&format!("{}.class", class.replace('.', "/")))?;
+        if bytes.len() < 8 || bytes[..4] != [0xCA, 0xFE, 0xBA, 0xBE] {
+            return None;
+        }
+        let major = u16::from_be_bytes([bytes[6], bytes[7]]) as u32;
+        major.checked_sub(44)
+    }
+}
@@ -0,0 +1,119 @@
+use std::fmt::{self, Display};
+use std::process::Command;
+
+mod discovery;
+
+pub use discovery::{JavaDiscovery, JavaInstallation};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JavaVersion {
+    pub version: String,
+    pub vendor: Option<String>,
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+    pub arch: Option<String>,
+    pub is_64bit: bool,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum JavaProbeError {
+    #[error("failed to execute Java: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("unrecognized Java version output: {0}")]
+    UnrecognizedOutput(String),
+}
+
+impl JavaVersion {
+    pub fn probe<T: AsRef<std::ffi::OsStr>>(java_path: T) -> Result<JavaVersion, JavaProbeError> {
+        let output = Command::new(&java_path)
+            .arg("-XshowSettings:properties")
+            .arg("-version")
+            .output()?;
+        let text = String::from_utf8_lossy(&output.stderr).into_owned();
+        if let Some(version) = Self::parse_properties(&text) {
+            return Ok(version);
+        }
+
+        // Some older or non-HotSpot JVMs reject -XshowSettings, fall back to plain -version.
+        let output = Command::new(&java_path).arg("-version").output()?;
+        let text = String::from_utf8_lossy(&output.stderr).into_owned();
+        Self::parse_version_output(&text).ok_or(JavaProbeError::UnrecognizedOutput(text))
+    }
+
+    pub fn parse_properties(output: &str) -> Option<JavaVersion> {
+        let property = |name: &str| {
+            output.lines().find_map(|line| {
+                let (key, value) = line.split_once(" = ")?;
+                (key.trim() == name).then(|| value.trim().to_string())
+            })
+        };
+
+        let version = property("java.version")?;
+        let (major, minor, patch) = parse_version_string(&version)?;
+        let arch = property("os.arch");
+        let is_64bit = match property("sun.arch.data.model") {
+            Some(model) => model == "64",
+            None => arch.as_deref().is_some_and(|arch| arch.contains("64")),
+        };
+
+        Some(JavaVersion {
+            version,
+            vendor: property("java.vendor"),
+            major,
+            minor,
+            patch,
+            arch,
+            is_64bit,
+        })
+    }
+
+    pub fn parse_version_output(output: &str) -> Option<JavaVersion> {
+        let version = output.lines().find_map(|line| {
+            let (_, rest) = line.split_once(" version \"")?;
+            Some(rest.split('"').next()?.to_string())
+        })?;
+        let (major, minor, patch) = parse_version_string(&version)?;
+
+        Some(JavaVersion {
+            version,
+            vendor: None,
+            major,
+            minor,
+            patch,
+            arch: None,
+            is_64bit: output.contains("64-Bit"),
+        })
+    }
+}
+
+impl Display for JavaVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Java {} ({})", self.major, self.version)?;
+        if let Some(vendor) = &self.vendor {
+            write!(f, " {}", vendor)?;
+        }
+        if let Some(arch) = &self.arch {
+            write!(f, " {}", arch)?;
+        }
+        Ok(())
+    }
+}
+
+// Handles both the legacy `1.8.0_382` scheme and the JEP 223 `17.0.8` scheme.
+fn parse_version_string(version: &str) -> Option<(u32, u32, u32)> {
+    let version = version.split(['-', '+']).next()?;
+    if let Some(legacy) = version.strip_prefix("1.") {
+        let (version, update) = legacy.split_once('_').unwrap_or((legacy, "0"));
+        let mut parts = version.split('.');
+        let major = parts.next()?.parse().ok()?;
+        let minor = parts.next().unwrap_or("0").parse().ok()?;
+        return Some((major, minor, update.parse().ok()?));
+    }
+
+    let mut parts = version.split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next().unwrap_or("0").parse().ok()?;
+    let patch = parts.next().unwrap_or("0").parse().ok()?;
+    Some((major, minor, patch))
+}
@@ -0,0 +1,105 @@
+use std::cmp::Reverse;
+use std::collections::HashSet;
+use std::env;
+use std::path::{Path, PathBuf};
+
+use super::JavaVersion;
+
+#[cfg(windows)]
+const JAVA_EXECUTABLE: &str = "java.exe";
+#[cfg(not(windows))]
+const JAVA_EXECUTABLE: &str = "java";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JavaInstallation {
+    pub path: PathBuf,
+    pub version: JavaVersion,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct JavaDiscovery {
+    search_dirs: Vec<PathBuf>,
+}
+
+impl JavaDiscovery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn search_dir<P: Into<PathBuf>>(mut self, dir: P) -> Self {
+        self.search_dirs.push(dir.into());
+        self
+    }
+
+    pub fn candidates(&self) -> Vec<PathBuf> {
+        let mut candidates = Vec::new();
+
+        if let Some(java_home) = env::var_os("JAVA_HOME") {
+            candidates.push(java_home_executable(Path::new(&java_home)));
+        }
+        if let Some(path) = env::var_os("PATH") {
+            candidates.extend(env::split_paths(&path).map(|dir| dir.join(JAVA_EXECUTABLE)));
+        }
+
+        let mut roots: Vec<PathBuf> = self.search_dirs.clone();
+        roots.extend(
+            ["/usr/lib/jvm", "/usr/lib64/jvm", "/usr/java", "/Library/Java/JavaVirtualMachines"]
+                .map(PathBuf::from),
+        );
+        let home = env::var_os("HOME").or_else(|| env::var_os("USERPROFILE")).map(PathBuf::from);
+        let tool_dir = |var: &str, default: &str| {
+            env::var_os(var)
+                .map(PathBuf::from)
+                .or_else(|| home.as_ref().map(|home| home.join(default)))
+        };
+        roots.extend(tool_dir("SDKMAN_DIR", ".sdkman").map(|dir| dir.join("candidates/java")));
+        roots.extend(tool_dir("ASDF_DATA_DIR", ".asdf").map(|dir| dir.join("installs/java")));
+        roots.extend(tool_dir("JABBA_HOME", ".jabba").map(|dir| dir.join("jdk")));
+
+        for root in roots {
+            let Ok(entries) = std::fs::read_dir(&root) else {
+                continue;
+            };
+            let mut homes: Vec<PathBuf> = entries.filter_map(|entry| Some(entry.ok()?.path())).collect();
+            homes.sort();
+            candidates.extend(homes.iter().map(|home| java_home_executable(home)));
+        }
+
+        let mut seen = HashSet::new();
+        candidates
+            .into_iter()
+            .filter_map(|candidate| candidate.canonicalize().ok())
+            .filter(|candidate| candidate.is_file() && seen.insert(candidate.clone()))
+            .collect()
+    }
+
+    pub fn scan(&self) -> Vec<JavaInstallation> {
+        let mut installations: Vec<JavaInstallation> = self
+            .candidates()
+            .into_iter()
+            .filter_map(|path| {
+                let version = JavaVersion::probe(&path).ok()?;
+                Some(JavaInstallation { path, version })
+            })
+            .collect();
+        installations.sort_by_key(|installation| {
+            let version = &installation.version;
+            Reverse((version.major, version.minor, version.patch))
+        });
+        installations
+    }
+
+    pub fn find<F: Fn(&JavaVersion) -> bool>(&self, predicate: F) -> Option<JavaInstallation> {
+        self.scan().into_iter().find(|installation| predicate(&installation.version))
+    }
+}
+
+// Handles both plain JDK layouts and macOS bundles with a `Contents/Home` directory.
+fn java_home_executable(home: &Path) -> PathBuf {
+    let bundle = home.join("Contents/Home/bin").join(JAVA_EXECUTABLE);
+    if bundle.exists() {
+        bundle
+    } else {
+        home.join("bin").join(JAVA_EXECUTABLE)
+    }
+}
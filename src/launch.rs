@@ -0,0 +1,168 @@
+use std::path::Path;
+
+pub const USER_JVM_ARGS_FILE: &str = "user_jvm_args.txt";
+
+#[cfg(windows)]
+const CLASSPATH_SEPARATOR: &str = ";";
+#[cfg(not(windows))]
+const CLASSPATH_SEPARATOR: &str = ":";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LaunchTarget {
+    Jar(String),
+    MainClass { classpath: Vec<String>, main_class: String },
+    ArgFiles(Vec<String>),
+}
+
+impl LaunchTarget {
+    pub fn jar(&self) -> Option<&str> {
+        match self {
+            LaunchTarget::Jar(jar) => Some(jar),
+            _ => None,
+        }
+    }
+
+    pub fn args(&self) -> Vec<String> {
+        match self {
+            LaunchTarget::Jar(jar) => vec!["-jar".to_string(), jar.clone()],
+            LaunchTarget::MainClass { classpath, main_class } => {
+                let mut args = Vec::new();
+                if !classpath.is_empty() {
+                    args.push("-cp".to_string());
+                    args.push(classpath.join(CLASSPATH_SEPARATOR));
+                }
+                args.push(main_class.clone());
+                args
+            }
+            LaunchTarget::ArgFiles(files) => files.iter().map(|file| format!("@{}", file)).collect(),
+        }
+    }
+
+    // `user_jvm_args.txt` is merged into the JVM arguments instead of being passed as an argfile.
+    pub(crate) fn without_user_jvm_args(self) -> Self {
+        match self {
+            LaunchTarget::ArgFiles(files) => LaunchTarget::ArgFiles(
+                files
+                    .into_iter()
+                    .map(|file| file.trim_start_matches('@').to_string())
+                    .filter(|file| Path::new(file) != Path::new(USER_JVM_ARGS_FILE))
+                    .collect(),
+            ),
+            target => target,
+        }
+    }
+}
+
+impl From<String> for LaunchTarget {
+    fn from(jar: String) -> Self {
+        LaunchTarget::Jar(jar)
+    }
+}
+
+impl From<&str> for LaunchTarget {
+    fn from(jar: &str) -> Self {
+        LaunchTarget::Jar(jar.to_string())
+    }
+}
+
+pub(crate) fn read_user_jvm_args(server_path: &Path) -> Result<Vec<String>, std::io::Error> {
+    match std::fs::read_to_string(server_path.join(USER_JVM_ARGS_FILE)) {
+        Ok(contents) => Ok(parse_arg_file(&contents)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e),
+    }
+}
+
+// Follows the java launcher's @argfile rules: whitespace separated, quotes group, `#` starts a comment.
+// Backslashes only escape inside quotes, where one at the end of a line continues the argument on the next.
+fn parse_arg_file(contents: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_arg = false;
+    let mut quote = None;
+    for line in contents.lines() {
+        let mut continued = false;
+        let mut chars = line.trim_start().chars();
+        while let Some(c) = chars.next() {
+            match (quote, c) {
+                (None, '#') if !in_arg => break,
+                (None, '"' | '\'') => {
+                    quote = Some(c);
+                    in_arg = true;
+                }
+                (Some(q), c) if c == q => quote = None,
+                (None, c) if c.is_whitespace() => {
+                    if in_arg {
+                        args.push(std::mem::take(&mut current));
+                        in_arg = false;
+                    }
+                }
+                (Some(_), '\\') => match chars.next() {
+                    Some('n') => current.push('\n'),
+                    Some('r') => current.push('\r'),
+                    Some('t') => current.push('\t'),
+                    Some('f') => current.push('\u{c}'),
+                    Some(next) => current.push(next),
+                    None => continued = true,
+                },
+                (_, c) => {
+                    current.push(c);
+                    in_arg = true;
+                }
+            }
+        }
+        if continued {
+            continue;
+        }
+        quote = None;
+        if in_arg {
+            args.push(std::mem::take(&mut current));
+            in_arg = false;
+        }
+    }
+    if in_arg {
+        args.push(current);
+    }
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_on_whitespace_and_skips_comments() {
+        let contents = "# Xmx is set by the launcher\n-Xms1G   -XX:+UseG1GC\n\n  -Dfile.encoding=UTF-8 # trailing\n";
+        assert_eq!(parse_arg_file(contents), ["-Xms1G", "-XX:+UseG1GC", "-Dfile.encoding=UTF-8"]);
+    }
+
+    #[test]
+    fn quotes_group_whitespace() {
+        let contents = "-Dname=\"My Server\" '-Dmotd=a # b'";
+        assert_eq!(parse_arg_file(contents), ["-Dname=My Server", "-Dmotd=a # b"]);
+    }
+
+    #[test]
+    fn keeps_backslashes_outside_quotes() {
+        let contents = r"-Djava.io.tmpdir=C:\server\tmp -Dpath=a\ b";
+        assert_eq!(parse_arg_file(contents), [r"-Djava.io.tmpdir=C:\server\tmp", r"-Dpath=a\", "b"]);
+    }
+
+    #[test]
+    fn processes_escapes_inside_quotes() {
+        let contents = r#""-Dsep=\t" "-Ddir=C:\\server" "-Dquote=\"""#;
+        assert_eq!(parse_arg_file(contents), ["-Dsep=\t", r"-Ddir=C:\server", "-Dquote=\""]);
+    }
+
+    #[test]
+    fn continues_quoted_arguments_on_the_next_line() {
+        let contents = "\"-Dlist=a,\\\n    b,c\" -Xmx2G";
+        assert_eq!(parse_arg_file(contents), ["-Dlist=a,b,c", "-Xmx2G"]);
+    }
+
+    #[test]
+    fn ends_unterminated_quotes_at_the_line_end() {
+        let contents = "\"-Dname=open\n-Xmx2G";
+        assert_eq!(parse_arg_file(contents), ["-Dname=open", "-Xmx2G"]);
+    }
+}
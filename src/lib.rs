@@ -1,21 +1,88 @@
 use std::{fmt::Debug, process::{Command, Stdio}};
 
+use jar::ServerJar;
+use memory::HeapFlags;
+
+mod command;
+mod command_line;
+mod eula;
+mod flavor;
+mod handle;
+mod jar;
+mod java;
+mod launch;
+mod log;
+mod memory;
+mod output;
+mod ping;
+mod preset;
+mod properties;
+mod query;
+mod rcon;
+mod supervisor;
+mod text;
+mod version;
+
+pub use command::{ServerCommand, TeleportDestination, Weather, WhitelistAction};
+pub use command_line::CommandLine;
+pub use flavor::{ServerFlavor, ServerKind};
+pub use handle::{
+    ExitReport, SendCommandError, ServerExitStatus, ServerHandle, StopOutcome, StopPolicy, StopStage,
+    WaitReadyError,
+};
+pub use jar::JavaRequirement;
+pub use java::{JavaDiscovery, JavaInstallation, JavaProbeError, JavaVersion};
+pub use launch::LaunchTarget;
+pub use log::{AdvancementKind, LogLine, LogParser, ServerEvent};
+pub use memory::ByteSize;
+pub use output::{OutputLine, OutputStream};
+pub use ping::{PingError, PlayerSample, ServerPing, ServerStatus};
+pub use preset::JvmPreset;
+pub use properties::{Difficulty, GameMode, ServerProperties};
+pub use query::{BasicStat, FullStat, QueryClient, QueryError};
+pub use rcon::{RconClient, RconError};
+pub use supervisor::{RestartEvent, RestartPolicy, Supervisor, SupervisorControl, SupervisorError, SupervisorExit};
+pub use text::{TextColor, TextComponent, TextContent, TextStyle};
+pub use version::MinecraftVersion;
+
+const DEFAULT_OUTPUT_TAIL_LINES: usize = 100;
+
 pub struct MinecraftServerBuilder {
     server_path: Option<String>,
-    server_jar: Option<String>,
+    launch_target: Option<LaunchTarget>,
     java_path: Option<String>,
     java_args: Option<Vec<String>>,
+    min_memory: Option<ByteSize>,
+    max_memory: Option<ByteSize>,
+    jvm_preset: Option<JvmPreset>,
+    server_args: Option<Vec<String>>,
+    env: Option<Vec<(String, String)>>,
     gui: Option<bool>,
+    piped_stdin: Option<bool>,
+    capture_output: Option<bool>,
+    echo_output: Option<bool>,
+    output_tail_lines: Option<usize>,
+    accept_eula: Option<bool>,
 }
 
 impl MinecraftServerBuilder {
     pub fn new() -> Self {
         MinecraftServerBuilder {
             server_path: None,
-            server_jar: None,
+            launch_target: None,
             java_path: None,
             java_args: None,
+            min_memory: None,
+            max_memory: None,
+            jvm_preset: None,
+            server_args: None,
+            env: None,
             gui: None,
+            piped_stdin: None,
+            capture_output: None,
+            echo_output: None,
+            output_tail_lines: None,
+            accept_eula: None,
         }
     }
 
@@ -25,7 +92,12 @@ impl MinecraftServerBuilder {
     }
 
     pub fn server_jar<T: Into<String>>(mut self, jar: T) -> Self {
-        self.server_jar = Some(jar.into());
+        self.launch_target = Some(LaunchTarget::Jar(jar.into()));
+        self
+    }
+
+    pub fn launch_target(mut self, target: LaunchTarget) -> Self {
+        self.launch_target = Some(target);
         self
     }
 
@@ -39,88 +111,463 @@ impl MinecraftServerBuilder {
         self
     }
 
+    pub fn min_memory(mut self, size: ByteSize) -> Self {
+        self.min_memory = Some(size);
+        self
+    }
+
+    pub fn max_memory(mut self, size: ByteSize) -> Self {
+        self.max_memory = Some(size);
+        self
+    }
+
+    pub fn jvm_preset(mut self, preset: JvmPreset) -> Self {
+        self.jvm_preset = Some(preset);
+        self
+    }
+
+    pub fn server_args<T: Into<String> + Clone>(mut self, args: &[T]) -> Self {
+        self.server_args = Some(args.iter().map(|s| s.clone().into()).collect());
+        self
+    }
+
+    pub fn port(self, port: u16) -> Self {
+        self.server_option("--port", port.to_string())
+    }
+
+    pub fn world<T: Into<String>>(self, world: T) -> Self {
+        self.server_option("--world", world.into())
+    }
+
+    pub fn universe<T: Into<String>>(self, universe: T) -> Self {
+        self.server_option("--universe", universe.into())
+    }
+
+    pub fn server_id<T: Into<String>>(self, id: T) -> Self {
+        self.server_option("--serverId", id.into())
+    }
+
+    pub fn force_upgrade(self, enabled: bool) -> Self {
+        self.server_flag("--forceUpgrade", enabled)
+    }
+
+    pub fn erase_cache(self, enabled: bool) -> Self {
+        self.server_flag("--eraseCache", enabled)
+    }
+
+    pub fn safe_mode(self, enabled: bool) -> Self {
+        self.server_flag("--safeMode", enabled)
+    }
+
+    pub fn init_settings(self, enabled: bool) -> Self {
+        self.server_flag("--initSettings", enabled)
+    }
+
+    pub fn demo(self, enabled: bool) -> Self {
+        self.server_flag("--demo", enabled)
+    }
+
+    pub fn bonus_chest(self, enabled: bool) -> Self {
+        self.server_flag("--bonusChest", enabled)
+    }
+
+    fn server_option(mut self, name: &str, value: String) -> Self {
+        let args = self.server_args.get_or_insert_with(Vec::new);
+        if let Some(index) = args.iter().position(|arg| arg == name) {
+            args.drain(index..(index + 2).min(args.len()));
+        }
+        args.push(name.to_string());
+        args.push(value);
+        self
+    }
+
+    fn server_flag(mut self, name: &str, enabled: bool) -> Self {
+        let args = self.server_args.get_or_insert_with(Vec::new);
+        args.retain(|arg| arg != name);
+        if enabled {
+            args.push(name.to_string());
+        }
+        self
+    }
+
+    pub fn env<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.env.get_or_insert_with(Vec::new).push((key.into(), value.into()));
+        self
+    }
+
     pub fn gui(mut self, gui: bool) -> Self {
         self.gui = Some(gui);
         self
     }
+
+    pub fn piped_stdin(mut self, piped: bool) -> Self {
+        self.piped_stdin = Some(piped);
+        self
+    }
+
+    pub fn capture_output(mut self, capture: bool) -> Self {
+        self.capture_output = Some(capture);
+        self
+    }
+
+    pub fn echo_output(mut self, echo: bool) -> Self {
+        self.echo_output = Some(echo);
+        self
+    }
+
+    // The tail is only kept while output is captured, an inherited console is never read.
+    pub fn output_tail_lines(mut self, lines: usize) -> Self {
+        self.output_tail_lines = Some(lines);
+        self
+    }
+
+    pub fn accept_eula(mut self, accept: bool) -> Self {
+        self.accept_eula = Some(accept);
+        self
+    }
     
     pub fn build(self) -> Result<MinecraftServer, MinecraftServerBuildError> {
         let server_path = self.server_path.ok_or(MinecraftServerBuildError::MissingServerPath)?;
-        let server_jar = self.server_jar.ok_or(MinecraftServerBuildError::MissingServerJar)?;
-        
+        let launch_target = self.launch_target.ok_or(MinecraftServerBuildError::MissingServerJar)?;
+
         if !std::path::Path::new(&server_path).exists() {
             return Err(MinecraftServerBuildError::InvalidServerPath(server_path));
         }
+
+        let server_dir = std::path::Path::new(&server_path);
+        let launch_target = launch_target.without_user_jvm_args();
+        let jar_path = launch_target.jar().map(|jar| server_dir.join(jar));
+        if let Some(jar_path) = &jar_path {
+            if !jar_path.is_file() {
+                return Err(MinecraftServerBuildError::ServerJarNotFound(jar_path.display().to_string()));
+            }
+            let mut jar = ServerJar::open(jar_path).map_err(|e| MinecraftServerBuildError::UnreadableServerJar {
+                path: jar_path.display().to_string(),
+                reason: e.to_string(),
+            })?;
+            if !jar.is_executable() {
+                return Err(MinecraftServerBuildError::NonExecutableServerJar(jar_path.display().to_string()));
+            }
+        }
+        if let LaunchTarget::ArgFiles(files) = &launch_target
+            && let Some(missing) = files.iter().find(|file| !server_dir.join(file).is_file())
+        {
+            return Err(MinecraftServerBuildError::ArgFileNotFound(missing.clone()));
+        }
+
+        let write_eula = !eula::is_accepted(server_dir);
+        if write_eula && !self.accept_eula.unwrap_or(false) {
+            return Err(MinecraftServerBuildError::EulaNotAccepted(server_path));
+        }
         
-        let java_path = self.java_path.unwrap_or("java".to_string());
-        if Command::new(&java_path).arg("--version").output().is_err() {
+        let mut java_args = self.java_args.unwrap_or_default();
+        if let LaunchTarget::ArgFiles(_) = &launch_target {
+            // Arguments given to the builder take precedence over the ones in user_jvm_args.txt.
+            let explicit = HeapFlags::from_args(&java_args).unwrap_or_default();
+            let explicit_min = explicit.min.is_some() || self.min_memory.is_some();
+            let explicit_max = explicit.max.is_some() || self.max_memory.is_some();
+            let mut merged: Vec<String> = launch::read_user_jvm_args(server_dir)?
+                .into_iter()
+                .filter(|arg| {
+                    let HeapFlags { min, max } = HeapFlags::from_args(std::slice::from_ref(arg)).unwrap_or_default();
+                    let gc = arg.starts_with("-XX:+Use") && arg.ends_with("GC");
+                    !((min.is_some() && explicit_min)
+                        || (max.is_some() && explicit_max)
+                        || (gc && self.jvm_preset.is_some()))
+                })
+                .collect();
+            merged.append(&mut java_args);
+            java_args = merged;
+        }
+        let heap = HeapFlags::from_args(&java_args).map_err(MinecraftServerBuildError::ConflictingJavaArg)?;
+        if let (Some(_), Some(size)) = (heap.min, self.min_memory) {
+            return Err(MinecraftServerBuildError::ConflictingJavaArg(format!("-Xms{}", size)));
+        }
+        if let (Some(_), Some(size)) = (heap.max, self.max_memory) {
+            return Err(MinecraftServerBuildError::ConflictingJavaArg(format!("-Xmx{}", size)));
+        }
+        if self.jvm_preset.is_some()
+            && let Some(gc) = java_args.iter().find(|arg| arg.starts_with("-XX:+Use") && arg.ends_with("GC"))
+        {
+            return Err(MinecraftServerBuildError::ConflictingJavaArg(gc.clone()));
+        }
+        let min_memory = self.min_memory.or(heap.min);
+        let max_memory = self.max_memory.or(heap.max);
+        if let (Some(min), Some(max)) = (min_memory, max_memory)
+            && min > max
+        {
+            return Err(MinecraftServerBuildError::InvalidMemory { min, max });
+        }
+        if let (Some(requested), Some(available)) = (max_memory.or(min_memory), ByteSize::physical_memory())
+            && requested > available
+        {
+            return Err(MinecraftServerBuildError::InsufficientMemory { requested, available });
+        }
+
+        let explicit_java = self.java_path.is_some();
+        let mut java_path = self.java_path.unwrap_or("java".to_string());
+        let mut java_version = match JavaVersion::probe(&java_path) {
+            Ok(version) => Some(version),
+            Err(JavaProbeError::Io(_)) if !explicit_java => None,
+            Err(JavaProbeError::Io(_)) => return Err(MinecraftServerBuildError::InvalidJavaPath(java_path)),
+            Err(JavaProbeError::UnrecognizedOutput(output)) => {
+                return Err(MinecraftServerBuildError::UnrecognizedJavaVersion { java_path, output });
+            }
+        };
+
+        let flavor = ServerFlavor::detect(server_dir, &launch_target);
+        let mut jar = jar_path.as_ref().and_then(|jar_path| ServerJar::open(jar_path).ok());
+        let java_requirement = jar
+            .as_mut()
+            .and_then(JavaRequirement::from_version_json)
+            .or_else(|| flavor.minecraft().as_ref().map(JavaRequirement::from_minecraft_version))
+            .or_else(|| jar.as_mut().and_then(JavaRequirement::from_main_class));
+        if let Some(required) = java_requirement {
+            let compatible = java_version.as_ref().is_some_and(|version| required.is_satisfied_by(version));
+            if !compatible {
+                let found = java_version.as_ref().map(|version| version.major);
+                let installation = if explicit_java {
+                    None
+                } else {
+                    JavaDiscovery::new().find(|version| required.is_satisfied_by(version))
+                };
+                match installation {
+                    Some(installation) => {
+                        java_path = installation.path.to_string_lossy().into_owned();
+                        java_version = Some(installation.version);
+                    }
+                    None => return Err(MinecraftServerBuildError::IncompatibleJava { required, found }),
+                }
+            }
+        }
+        let Some(java_version) = java_version else {
             return Err(MinecraftServerBuildError::InvalidJavaPath(java_path));
+        };
+
+        // Only touch the server directory once every check has passed.
+        if write_eula {
+            eula::accept(server_dir).map_err(MinecraftServerBuildError::EulaWriteFailed)?;
         }
 
         Ok(MinecraftServer {
             server_path,
-            server_jar,
+            launch_target,
+            flavor: Some(flavor),
             java_path,
-            java_args: self.java_args.unwrap_or_default(),
+            java_version: Some(java_version),
+            java_requirement,
+            java_args,
+            min_memory: self.min_memory,
+            max_memory: self.max_memory,
+            jvm_preset: self.jvm_preset,
+            server_args: self.server_args.unwrap_or_default(),
+            env: self.env.unwrap_or_default(),
             gui: self.gui.unwrap_or(false),
+            piped_stdin: self.piped_stdin.unwrap_or(false),
+            capture_output: self.capture_output.unwrap_or(false),
+            echo_output: self.echo_output.unwrap_or(true),
+            output_tail_lines: self.output_tail_lines.unwrap_or(DEFAULT_OUTPUT_TAIL_LINES),
         })
     }
 }
 
+impl Default for MinecraftServerBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum MinecraftServerBuildError {
     #[error("server path is missing")]
     MissingServerPath,
-    #[error("server jar file is missing")]
+    #[error("server jar or launch target is missing")]
     MissingServerJar,
     #[error("invalid server path: {0}")]
     InvalidServerPath(String),
+    #[error("server jar not found: {0}")]
+    ServerJarNotFound(String),
+    #[error("unreadable server jar {path}: {reason}")]
+    UnreadableServerJar { path: String, reason: String },
+    #[error("server jar has no Main-Class: {0}")]
+    NonExecutableServerJar(String),
+    #[error("argument file not found: {0}")]
+    ArgFileNotFound(String),
+    #[error("EULA has not been accepted in {0}, see https://aka.ms/MinecraftEULA")]
+    EulaNotAccepted(String),
+    #[error("failed to write eula.txt: {0}")]
+    EulaWriteFailed(std::io::Error),
     #[error("invalid Java path: {0}")]
     InvalidJavaPath(String),
+    #[error("unrecognized version output from {java_path}: {output}")]
+    UnrecognizedJavaVersion { java_path: String, output: String },
+    #[error("server requires {required}, found {}", .found.map_or("no Java".to_string(), |major| format!("Java {}", major)))]
+    IncompatibleJava { required: JavaRequirement, found: Option<u32> },
+    #[error("conflicting Java argument: {0}")]
+    ConflictingJavaArg(String),
+    #[error("minimum memory {min} exceeds maximum memory {max}")]
+    InvalidMemory { min: ByteSize, max: ByteSize },
+    #[error("requested memory {requested} exceeds physical memory {available}")]
+    InsufficientMemory { requested: ByteSize, available: ByteSize },
     #[error("failed to execute command: {0}")]
     CommandExecutionError(#[from] std::io::Error),
 }
 
+#[derive(Debug, thiserror::Error)]
+pub enum MinecraftServerRunError {
+    #[error("server exited abnormally with {}", .0.status)]
+    AbnormalExit(ExitReport),
+    #[error("failed to run server: {0}")]
+    Io(#[from] std::io::Error),
+}
+
 pub struct MinecraftServer {
     pub server_path: String,
-    pub server_jar: String,
+    pub launch_target: LaunchTarget,
+    pub flavor: Option<ServerFlavor>,
     pub java_path: String,
+    pub java_version: Option<JavaVersion>,
+    pub java_requirement: Option<JavaRequirement>,
     pub java_args: Vec<String>,
+    pub min_memory: Option<ByteSize>,
+    pub max_memory: Option<ByteSize>,
+    pub jvm_preset: Option<JvmPreset>,
+    pub server_args: Vec<String>,
+    pub env: Vec<(String, String)>,
     pub gui: bool,
+    pub piped_stdin: bool,
+    pub capture_output: bool,
+    pub echo_output: bool,
+    pub output_tail_lines: usize,
 }
 
 impl MinecraftServer {
     pub fn new<T: Into<String> + Clone>(server_path: T, server_jar: T, java_path: T, java_args: &[T], gui: bool) -> Self {
         MinecraftServer {
             server_path: server_path.into(),
-            server_jar: server_jar.into(),
+            launch_target: LaunchTarget::Jar(server_jar.into()),
+            flavor: None,
             java_path: java_path.into(),
+            java_version: None,
+            java_requirement: None,
             java_args: java_args.iter().map(|s| s.clone().into()).collect(),
+            min_memory: None,
+            max_memory: None,
+            jvm_preset: None,
+            server_args: Vec::new(),
+            env: Vec::new(),
             gui,
+            piped_stdin: false,
+            capture_output: false,
+            echo_output: true,
+            output_tail_lines: DEFAULT_OUTPUT_TAIL_LINES,
+        }
+    }
+
+    pub fn run(&mut self) -> Result<ExitReport, MinecraftServerRunError> {
+        let mut handle = self.spawn()?;
+        let report = handle.wait_with_report()?;
+        if report.is_abnormal() {
+            return Err(MinecraftServerRunError::AbnormalExit(report));
         }
+        Ok(report)
     }
 
-    pub fn run(&mut self) -> Result<(), std::io::Error> {
-        let mut server = self.get_command()
-            .stdin(Stdio::inherit())
-            .stdout(Stdio::inherit())
-            .stderr(Stdio::inherit())
+    pub fn spawn(&self) -> Result<ServerHandle, std::io::Error> {
+        let stdin = if self.piped_stdin { Stdio::piped() } else { Stdio::inherit() };
+        let output = || if self.capture_output { Stdio::piped() } else { Stdio::inherit() };
+        let child = self.get_command()
+            .stdin(stdin)
+            .stdout(output())
+            .stderr(output())
             .spawn()?;
-        let _ = server.wait()?;
-        Ok(())
+        Ok(ServerHandle::new(
+            child,
+            self.echo_output,
+            self.output_tail_lines,
+            self.flavor.as_ref().and_then(ServerFlavor::minecraft),
+        ))
+    }
+
+    pub fn properties(&self) -> Result<ServerProperties, std::io::Error> {
+        ServerProperties::load_from_dir(&self.server_path)
+    }
+
+    pub fn rcon(&self) -> Result<RconClient, RconError> {
+        let mut rcon = RconClient::from_properties(&self.properties().map_err(RconError::Properties)?)?;
+        rcon.set_minecraft_version(self.flavor.as_ref().and_then(ServerFlavor::minecraft));
+        Ok(rcon)
+    }
+
+    pub fn query(&self) -> Result<QueryClient, QueryError> {
+        QueryClient::from_properties(&self.properties().map_err(QueryError::Properties)?)
+    }
+
+    pub fn status(&self) -> Result<ServerStatus, PingError> {
+        let properties = self.properties().ok();
+        let port = self
+            .server_args
+            .iter()
+            .position(|arg| arg == "--port")
+            .and_then(|index| self.server_args.get(index + 1)?.parse().ok())
+            .or_else(|| properties.as_ref()?.server_port())
+            .unwrap_or(ping::DEFAULT_SERVER_PORT);
+        let host = properties
+            .as_ref()
+            .and_then(|properties| properties.server_ip())
+            .unwrap_or("127.0.0.1");
+        ServerPing::new(host, port).status()
+    }
+
+    pub fn command_line(&self) -> CommandLine {
+        let mut args = Vec::new();
+        if let Some(min) = self.min_memory {
+            args.push(format!("-Xms{}", min));
+        }
+        if let Some(max) = self.max_memory {
+            args.push(format!("-Xmx{}", max));
+        }
+        if let Some(preset) = self.jvm_preset {
+            let heap = HeapFlags::from_args(&self.java_args).unwrap_or_default();
+            let min = self.min_memory.or(heap.min);
+            let max = self.max_memory.or(heap.max);
+            args.extend(preset.flags(min, max, self.java_version.as_ref()));
+        }
+        args.extend(self.java_args.iter().cloned());
+        args.extend(self.launch_target.args());
+        if !self.gui {
+            args.push("--nogui".to_string());
+        }
+        args.extend(self.server_args.iter().cloned());
+
+        CommandLine {
+            program: self.java_path.clone(),
+            args,
+            working_dir: self.server_path.clone(),
+            env: self.env.clone(),
+        }
+    }
+
+    pub fn write_run_script(&self) -> Result<std::path::PathBuf, std::io::Error> {
+        let path = std::path::Path::new(&self.server_path).join("run.sh");
+        if let Ok(existing) = std::fs::read_to_string(&path)
+            && !existing.contains(command_line::RUN_SCRIPT_MARKER)
+        {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                format!("refusing to overwrite {}", path.display()),
+            ));
+        }
+        std::fs::write(&path, self.command_line().to_run_script())?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+        }
+        Ok(path)
     }
 
     fn get_command(&self) -> Command {
-        let mut command = Command::new(&self.java_path);
-        command
-            .args(self.java_args.clone())
-            .arg("-jar")
-            .arg(&self.server_jar)
-            .current_dir(&self.server_path);
-
-            if !self.gui {
-                command.arg("--nogui");
-            }
-        command
+        self.command_line().to_command()
     }
 }
\ No newline at end of file
@@ -0,0 +1,428 @@
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use crate::TextComponent;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogLine {
+    pub time: String,
+    pub thread: Option<String>,
+    pub level: String,
+    pub logger: Option<String>,
+    pub message: String,
+}
+
+impl LogLine {
+    // Accepts the vanilla `[12:34:56] [Server thread/INFO]: ...` prefix as well as the
+    // Paper/Spigot `[12:34:56 INFO]: ...`, Forge `[...] [Server thread/INFO] [logger/]: ...`
+    // and Fabric `[12:34:56] [Server thread/INFO] (Minecraft) ...` variants.
+    pub fn parse(line: &str) -> Option<LogLine> {
+        let line = strip_ansi(line);
+        let (head, rest) = bracketed(&line)?;
+
+        if let Some((time, level)) = head.rsplit_once(' ')
+            && is_level(level)
+        {
+            let message = rest.strip_prefix(": ").or_else(|| rest.strip_prefix(' '))?;
+            return Some(LogLine {
+                time: time.to_string(),
+                thread: None,
+                level: level.to_string(),
+                logger: None,
+                message: message.to_string(),
+            });
+        }
+
+        let time = head.rsplit(' ').next()?;
+        let (source, rest) = bracketed(rest.strip_prefix(' ')?)?;
+        let (thread, level) = source.rsplit_once('/')?;
+        if !is_level(level) {
+            return None;
+        }
+
+        let (logger, message) = if let Some(message) = rest.strip_prefix(": ") {
+            (None, message)
+        } else if let Some(rest) = rest.strip_prefix(" [") {
+            let (logger, message) = rest.split_once("]: ")?;
+            (Some(logger.trim_end_matches('/')), message)
+        } else if let Some(rest) = rest.strip_prefix(" (") {
+            let (logger, message) = rest.split_once(") ")?;
+            (Some(logger), message)
+        } else {
+            (None, rest.strip_prefix(' ')?)
+        };
+
+        Some(LogLine {
+            time: time.to_string(),
+            thread: Some(thread.to_string()),
+            level: level.to_string(),
+            logger: logger.map(str::to_string),
+            message: message.to_string(),
+        })
+    }
+
+    // Plugins routinely log messages with section sign color codes.
+    pub fn text(&self) -> TextComponent {
+        TextComponent::from_legacy(&self.message)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdvancementKind {
+    Task,
+    Challenge,
+    Goal,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ServerEvent {
+    Starting { version: String },
+    Ready { startup: Duration },
+    PlayerJoined { name: String, uuid: Option<String> },
+    PlayerLeft { name: String, uuid: Option<String> },
+    Chat { player: String, message: String },
+    Death { player: String, message: String },
+    Advancement { player: String, kind: AdvancementKind, advancement: String },
+    CantKeepUp { behind: Duration, ticks: u64 },
+    Stopping,
+}
+
+#[derive(Debug, Default)]
+pub struct LogParser {
+    uuids: HashMap<String, String>,
+    online: HashSet<String>,
+}
+
+impl LogParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn online_players(&self) -> impl Iterator<Item = &str> {
+        self.online.iter().map(String::as_str)
+    }
+
+    pub fn parse(&mut self, line: &str) -> Option<ServerEvent> {
+        let line = LogLine::parse(line)?;
+        self.parse_line(&line)
+    }
+
+    pub fn parse_line(&mut self, line: &LogLine) -> Option<ServerEvent> {
+        let message = line.message.as_str();
+
+        if let Some(rest) = message.strip_prefix("UUID of player ")
+            && let Some((name, uuid)) = rest.split_once(" is ")
+        {
+            self.uuids.insert(name.to_string(), uuid.trim().to_string());
+            return None;
+        }
+        if let Some(version) = message.strip_prefix("Starting minecraft server version ") {
+            return Some(ServerEvent::Starting { version: version.trim().to_string() });
+        }
+        if let Some(startup) = parse_done(message) {
+            return Some(ServerEvent::Ready { startup });
+        }
+        if message.starts_with("Can't keep up!") {
+            return parse_cant_keep_up(message);
+        }
+        if message == "Stopping server" {
+            return Some(ServerEvent::Stopping);
+        }
+        if let Some(chat) = parse_chat(message) {
+            return Some(chat);
+        }
+        if let Some(name) = message.strip_suffix(" joined the game") {
+            let name = name.split(" (formerly known as ").next().unwrap_or(name);
+            self.online.insert(name.to_string());
+            return Some(ServerEvent::PlayerJoined {
+                name: name.to_string(),
+                uuid: self.uuids.get(name).cloned(),
+            });
+        }
+        if let Some(name) = message.strip_suffix(" left the game") {
+            self.online.remove(name);
+            return Some(ServerEvent::PlayerLeft {
+                name: name.to_string(),
+                uuid: self.uuids.remove(name),
+            });
+        }
+        if let Some(advancement) = parse_advancement(message) {
+            return Some(advancement);
+        }
+        if line.level == "INFO" {
+            return self.parse_death(message);
+        }
+        None
+    }
+
+    fn parse_death(&self, message: &str) -> Option<ServerEvent> {
+        let (player, rest) = message.split_once(' ')?;
+        if !self.online.contains(player) || rest.starts_with("was kicked") {
+            return None;
+        }
+        DEATH_PREFIXES.iter().find(|prefix| rest.starts_with(*prefix))?;
+        Some(ServerEvent::Death {
+            player: player.to_string(),
+            message: message.to_string(),
+        })
+    }
+}
+
+const DEATH_PREFIXES: &[&str] = &[
+    "was ",
+    "died",
+    "drowned",
+    "blew up",
+    "burned to death",
+    "discovered the floor was lava",
+    "didn't want to live",
+    "experienced kinetic energy",
+    "fell ",
+    "froze to death",
+    "hit the ground too hard",
+    "left the confines of this world",
+    "starved to death",
+    "suffocated in a wall",
+    "tried to swim in lava",
+    "walked into",
+    "went off with a bang",
+    "went up in flames",
+    "withered away",
+];
+
+fn parse_done(message: &str) -> Option<Duration> {
+    let rest = message.strip_prefix("Done (")?;
+    let (value, _) = rest.split_once(")!")?;
+    if let Some(nanos) = value.strip_suffix("ns") {
+        return nanos.parse().ok().map(Duration::from_nanos);
+    }
+    let seconds: f64 = value.strip_suffix('s')?.replace(',', ".").parse().ok()?;
+    Duration::try_from_secs_f64(seconds).ok()
+}
+
+fn parse_cant_keep_up(message: &str) -> Option<ServerEvent> {
+    let rest = &message[message.find("Running ")? + "Running ".len()..];
+    let (millis, rest) = rest.split_once("ms")?;
+    let rest = rest.trim_start_matches(" or ").trim_start_matches(" behind, skipping ");
+    let ticks = rest.split(' ').next()?;
+    Some(ServerEvent::CantKeepUp {
+        behind: Duration::from_millis(millis.parse().ok()?),
+        ticks: ticks.parse().ok()?,
+    })
+}
+
+fn parse_chat(message: &str) -> Option<ServerEvent> {
+    let message = message.strip_prefix("[Not Secure] ").unwrap_or(message);
+    let rest = message.strip_prefix('<')?;
+    let (player, text) = rest.split_once("> ")?;
+    if player.is_empty() || player.contains(' ') {
+        return None;
+    }
+    Some(ServerEvent::Chat {
+        player: player.to_string(),
+        message: text.to_string(),
+    })
+}
+
+fn parse_advancement(message: &str) -> Option<ServerEvent> {
+    const PATTERNS: &[(&str, AdvancementKind)] = &[
+        (" has made the advancement [", AdvancementKind::Task),
+        (" has just earned the achievement [", AdvancementKind::Task),
+        (" has completed the challenge [", AdvancementKind::Challenge),
+        (" has reached the goal [", AdvancementKind::Goal),
+    ];
+    let advancement = message.strip_suffix(']')?;
+    PATTERNS.iter().find_map(|(pattern, kind)| {
+        let (player, advancement) = advancement.split_once(pattern)?;
+        Some(ServerEvent::Advancement {
+            player: player.to_string(),
+            kind: *kind,
+            advancement: advancement.to_string(),
+        })
+    })
+}
+
+fn bracketed(s: &str) -> Option<(&str, &str)> {
+    let rest = s.strip_prefix('[')?;
+    let end = rest.find(']')?;
+    Some((&rest[..end], &rest[end + 1..]))
+}
+
+fn is_level(s: &str) -> bool {
+    matches!(s, "TRACE" | "DEBUG" | "INFO" | "WARN" | "ERROR" | "FATAL")
+}
+
+fn strip_ansi(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c == '\u{1b}' {
+            if chars.next() == Some('[') {
+                for c in chars.by_ref() {
+                    if c.is_ascii_alphabetic() {
+                        break;
+                    }
+                }
+            }
+            continue;
+        }
+        out.push(c);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn events(lines: &[&str]) -> Vec<ServerEvent> {
+        let mut parser = LogParser::new();
+        lines.iter().filter_map(|line| parser.parse(line)).collect()
+    }
+
+    #[test]
+    fn parses_log_prefixes() {
+        let vanilla = LogLine::parse("[12:34:56] [Server thread/INFO]: Stopping server").unwrap();
+        assert_eq!(vanilla.time, "12:34:56");
+        assert_eq!(vanilla.thread.as_deref(), Some("Server thread"));
+        assert_eq!(vanilla.level, "INFO");
+        assert_eq!(vanilla.message, "Stopping server");
+
+        let paper = LogLine::parse("[12:34:56 WARN]: Can't keep up!").unwrap();
+        assert_eq!((paper.thread, paper.level.as_str(), paper.message.as_str()), (None, "WARN", "Can't keep up!"));
+
+        let forge =
+            LogLine::parse("[01Jan2024 12:34:56.789] [Server thread/INFO] [net.minecraft.server.MinecraftServer/]: Hello")
+                .unwrap();
+        assert_eq!(forge.time, "12:34:56.789");
+        assert_eq!(forge.logger.as_deref(), Some("net.minecraft.server.MinecraftServer"));
+        assert_eq!(forge.message, "Hello");
+
+        let fabric = LogLine::parse("[12:34:56] [Server thread/INFO] (Minecraft) Hello").unwrap();
+        assert_eq!((fabric.logger.as_deref(), fabric.message.as_str()), (Some("Minecraft"), "Hello"));
+
+        assert_eq!(LogLine::parse("Loading libraries, please wait..."), None);
+    }
+
+    #[test]
+    fn strips_ansi_colors() {
+        let line = LogLine::parse("\u{1b}[32m[12:34:56 INFO]: \u{1b}[0;33mDone (3.5s)! For help, type \"help\"\u{1b}[m").unwrap();
+        assert_eq!(line.message, "Done (3.5s)! For help, type \"help\"");
+    }
+
+    #[test]
+    fn parses_startup_events() {
+        assert_eq!(
+            events(&[
+                "[12:00:00] [Server thread/INFO]: Starting minecraft server version 1.21.4",
+                "[12:00:05] [Server thread/INFO]: Done (5,123s)! For help, type \"help\"",
+                "[12:00:06 INFO]: Done (812345678ns)! For help, type \"help\"",
+                "[12:01:00] [Server thread/INFO]: Stopping server",
+            ]),
+            [
+                ServerEvent::Starting { version: "1.21.4".to_string() },
+                ServerEvent::Ready { startup: Duration::from_millis(5123) },
+                ServerEvent::Ready { startup: Duration::from_nanos(812345678) },
+                ServerEvent::Stopping,
+            ]
+        );
+    }
+
+    #[test]
+    fn tracks_players() {
+        let uuid = "8667ba71-b85a-4004-af54-457a9734eed7";
+        let mut parser = LogParser::new();
+        let lines = [
+            format!("[12:00:00] [User Authenticator #1/INFO]: UUID of player Steve is {}", uuid),
+            "[12:00:00] [Server thread/INFO]: Steve joined the game".to_string(),
+            "[12:00:01] [Server thread/INFO]: Alex (formerly known as Alex2) joined the game".to_string(),
+        ];
+        let joined: Vec<_> = lines.iter().filter_map(|line| parser.parse(line)).collect();
+        assert_eq!(
+            joined,
+            [
+                ServerEvent::PlayerJoined { name: "Steve".to_string(), uuid: Some(uuid.to_string()) },
+                ServerEvent::PlayerJoined { name: "Alex".to_string(), uuid: None },
+            ]
+        );
+        let mut online: Vec<_> = parser.online_players().collect();
+        online.sort();
+        assert_eq!(online, ["Alex", "Steve"]);
+
+        assert_eq!(
+            parser.parse("[12:00:02] [Server thread/INFO]: Steve left the game"),
+            Some(ServerEvent::PlayerLeft { name: "Steve".to_string(), uuid: Some(uuid.to_string()) })
+        );
+        assert_eq!(parser.online_players().collect::<Vec<_>>(), ["Alex"]);
+    }
+
+    #[test]
+    fn parses_chat_and_advancements() {
+        assert_eq!(
+            events(&[
+                "[12:00:00] [Server thread/INFO]: <Steve> hello <there>",
+                "[12:00:00] [Server thread/INFO]: [Not Secure] <Alex> hi",
+                "[12:00:00] [Server thread/INFO]: <not a player> hi",
+                "[12:00:01] [Server thread/INFO]: Steve has made the advancement [Stone Age]",
+                "[12:00:01] [Server thread/INFO]: Steve has completed the challenge [How Did We Get Here?]",
+                "[12:00:01] [Server thread/INFO]: Steve has reached the goal [The End?]",
+            ]),
+            [
+                ServerEvent::Chat { player: "Steve".to_string(), message: "hello <there>".to_string() },
+                ServerEvent::Chat { player: "Alex".to_string(), message: "hi".to_string() },
+                ServerEvent::Advancement {
+                    player: "Steve".to_string(),
+                    kind: AdvancementKind::Task,
+                    advancement: "Stone Age".to_string(),
+                },
+                ServerEvent::Advancement {
+                    player: "Steve".to_string(),
+                    kind: AdvancementKind::Challenge,
+                    advancement: "How Did We Get Here?".to_string(),
+                },
+                ServerEvent::Advancement {
+                    player: "Steve".to_string(),
+                    kind: AdvancementKind::Goal,
+                    advancement: "The End?".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_deaths_of_online_players_only() {
+        assert_eq!(
+            events(&[
+                "[12:00:00] [Server thread/INFO]: Zombie was slain by Steve",
+                "[12:00:00] [Server thread/INFO]: Steve joined the game",
+                "[12:00:01] [Server thread/INFO]: Steve was slain by Zombie",
+                "[12:00:02] [Server thread/INFO]: Steve was kicked for floating too long!",
+                "[12:00:03] [Server thread/INFO]: Steve fell from a high place",
+            ])[1..],
+            [
+                ServerEvent::Death {
+                    player: "Steve".to_string(),
+                    message: "Steve was slain by Zombie".to_string(),
+                },
+                ServerEvent::Death {
+                    player: "Steve".to_string(),
+                    message: "Steve fell from a high place".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_cant_keep_up() {
+        assert_eq!(
+            events(&[
+                "[12:00:00] [Server thread/WARN]: Can't keep up! Is the server overloaded? Running 5023ms or 100 ticks behind",
+                "[12:00:00 WARN]: Can't keep up! Did the system time change, or is the server overloaded? Running 2500ms behind, skipping 50 tick(s)",
+            ]),
+            [
+                ServerEvent::CantKeepUp { behind: Duration::from_millis(5023), ticks: 100 },
+                ServerEvent::CantKeepUp { behind: Duration::from_millis(2500), ticks: 50 },
+            ]
+        );
+    }
+}
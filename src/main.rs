@@ -1,6 +1,6 @@
-use mslc::{MinecraftServerBuilder, MinecraftServerBuildError};
+use mslc::MinecraftServerBuilder;
 
-fn main() -> Result<(), MinecraftServerBuildError> {
+fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut server = MinecraftServerBuilder::new()
         .java_path("invalid_java_path")
         .server_path("/home/orzmiku/mcserver/")
@@ -0,0 +1,107 @@
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+const KIB: u64 = 1024;
+const MIB: u64 = KIB * 1024;
+const GIB: u64 = MIB * 1024;
+const TIB: u64 = GIB * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ByteSize(u64);
+
+impl ByteSize {
+    pub const fn bytes(bytes: u64) -> Self {
+        ByteSize(bytes)
+    }
+
+    pub const fn kibibytes(kib: u64) -> Self {
+        ByteSize(kib * KIB)
+    }
+
+    pub const fn mebibytes(mib: u64) -> Self {
+        ByteSize(mib * MIB)
+    }
+
+    pub const fn gibibytes(gib: u64) -> Self {
+        ByteSize(gib * GIB)
+    }
+
+    pub const fn as_bytes(&self) -> u64 {
+        self.0
+    }
+
+    pub fn physical_memory() -> Option<ByteSize> {
+        let meminfo = std::fs::read_to_string("/proc/meminfo").ok()?;
+        let total = meminfo.lines().find_map(|line| line.strip_prefix("MemTotal:"))?;
+        let kib = total.trim().strip_suffix("kB")?.trim().parse().ok()?;
+        Some(ByteSize::kibibytes(kib))
+    }
+}
+
+impl FromStr for ByteSize {
+    type Err = String;
+
+    // Accepts the JVM's own notation (`4G`, `512m`, `1024k`, plain bytes) plus `GB`/`GiB` spellings.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let trimmed = s.trim();
+        let unit_start = trimmed.find(|c: char| !c.is_ascii_digit()).unwrap_or(trimmed.len());
+        let (number, unit) = trimmed.split_at(unit_start);
+        let number: u64 = number.parse().map_err(|_| format!("invalid size: {}", s))?;
+        let unit = unit.trim().to_ascii_lowercase();
+        let multiplier = match unit.trim_end_matches("ib").trim_end_matches('b') {
+            "" => 1,
+            "k" => KIB,
+            "m" => MIB,
+            "g" => GIB,
+            "t" => TIB,
+            _ => return Err(format!("invalid size: {}", s)),
+        };
+        number
+            .checked_mul(multiplier)
+            .map(ByteSize)
+            .ok_or_else(|| format!("size out of range: {}", s))
+    }
+}
+
+impl Display for ByteSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (unit, suffix) in [(TIB, "T"), (GIB, "G"), (MIB, "M"), (KIB, "K")] {
+            if self.0 >= unit && self.0.is_multiple_of(unit) {
+                return write!(f, "{}{}", self.0 / unit, suffix);
+            }
+        }
+        write!(f, "{}", self.0)
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct HeapFlags {
+    pub(crate) min: Option<ByteSize>,
+    pub(crate) max: Option<ByteSize>,
+}
+
+impl HeapFlags {
+    // Returns the offending argument when the same heap bound is given twice with different values.
+    pub(crate) fn from_args(args: &[String]) -> Result<Self, String> {
+        let mut flags = HeapFlags::default();
+        for arg in args {
+            let (slot, value) = if let Some(value) = arg.strip_prefix("-Xms") {
+                (&mut flags.min, value)
+            } else if let Some(value) = arg.strip_prefix("-XX:InitialHeapSize=") {
+                (&mut flags.min, value)
+            } else if let Some(value) = arg.strip_prefix("-Xmx") {
+                (&mut flags.max, value)
+            } else if let Some(value) = arg.strip_prefix("-XX:MaxHeapSize=") {
+                (&mut flags.max, value)
+            } else {
+                continue;
+            };
+            let size: ByteSize = value.parse().map_err(|_| arg.clone())?;
+            if slot.is_some_and(|existing| existing != size) {
+                return Err(arg.clone());
+            }
+            *slot = Some(size);
+        }
+        Ok(flags)
+    }
+}
@@ -0,0 +1,169 @@
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::thread::JoinHandle;
+use std::time::SystemTime;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+#[derive(Debug, Clone)]
+pub struct OutputLine {
+    pub stream: OutputStream,
+    pub line: String,
+    pub timestamp: SystemTime,
+}
+
+type Callback = Arc<Mutex<Box<dyn FnMut(&OutputLine) + Send>>>;
+
+#[derive(Clone)]
+enum Subscriber {
+    Callback(Callback),
+    Channel(Sender<OutputLine>),
+}
+
+#[derive(Default)]
+struct HubState {
+    subscribers: Vec<(u64, Subscriber)>,
+    next_id: u64,
+    tail: VecDeque<OutputLine>,
+}
+
+impl HubState {
+    fn add(&mut self, subscriber: Subscriber) {
+        self.subscribers.push((self.next_id, subscriber));
+        self.next_id += 1;
+    }
+}
+
+pub(crate) struct OutputHub {
+    echo: bool,
+    tail_capacity: usize,
+    state: Mutex<HubState>,
+}
+
+impl OutputHub {
+    pub(crate) fn new(echo: bool, tail_capacity: usize) -> Self {
+        OutputHub {
+            echo,
+            tail_capacity,
+            state: Mutex::new(HubState::default()),
+        }
+    }
+
+    pub(crate) fn on_output<F: FnMut(&OutputLine) + Send + 'static>(&self, callback: F) {
+        self.lock().add(Subscriber::Callback(Arc::new(Mutex::new(Box::new(callback)))));
+    }
+
+    pub(crate) fn subscribe(&self) -> Receiver<OutputLine> {
+        self.subscribe_with_tail().1
+    }
+
+    pub(crate) fn subscribe_with_tail(&self) -> (Vec<OutputLine>, Receiver<OutputLine>) {
+        let (sender, receiver) = mpsc::channel();
+        let mut state = self.lock();
+        state.add(Subscriber::Channel(sender));
+        (state.tail.iter().cloned().collect(), receiver)
+    }
+
+    pub(crate) fn tail(&self) -> Vec<OutputLine> {
+        self.lock().tail.iter().cloned().collect()
+    }
+
+    pub(crate) fn attach<R: Read + Send + 'static>(self: &Arc<Self>, reader: R, stream: OutputStream) -> JoinHandle<()> {
+        let hub = Arc::clone(self);
+        std::thread::spawn(move || {
+            let mut reader = BufReader::new(reader);
+            let mut buf = Vec::new();
+            loop {
+                buf.clear();
+                match reader.read_until(b'\n', &mut buf) {
+                    Ok(0) | Err(_) => break,
+                    Ok(_) => {}
+                }
+                let line = String::from_utf8_lossy(&buf);
+                let line = line.trim_end_matches(['\n', '\r']).to_string();
+                hub.publish(OutputLine {
+                    stream,
+                    line,
+                    timestamp: SystemTime::now(),
+                });
+            }
+        })
+    }
+
+    // Runs on the reader threads, which must keep draining the pipes whatever a subscriber does:
+    // callbacks run without the hub lock held and one that panics is dropped.
+    fn publish(&self, line: OutputLine) {
+        if self.echo {
+            let _ = match line.stream {
+                OutputStream::Stdout => writeln!(std::io::stdout(), "{}", line.line),
+                OutputStream::Stderr => writeln!(std::io::stderr(), "{}", line.line),
+            };
+        }
+
+        let subscribers = {
+            let mut state = self.lock();
+            if self.tail_capacity > 0 {
+                if state.tail.len() == self.tail_capacity {
+                    state.tail.pop_front();
+                }
+                state.tail.push_back(line.clone());
+            }
+            state.subscribers.clone()
+        };
+
+        let mut dropped = Vec::new();
+        for (id, subscriber) in subscribers {
+            let delivered = match subscriber {
+                Subscriber::Callback(callback) => {
+                    let mut callback = callback.lock().unwrap_or_else(PoisonError::into_inner);
+                    panic::catch_unwind(AssertUnwindSafe(|| callback(&line))).is_ok()
+                }
+                Subscriber::Channel(sender) => sender.send(line.clone()).is_ok(),
+            };
+            if !delivered {
+                dropped.push(id);
+            }
+        }
+        if !dropped.is_empty() {
+            self.lock().subscribers.retain(|(id, _)| !dropped.contains(id));
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HubState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[test]
+    fn a_panicking_callback_does_not_stop_the_reader() {
+        let hub = Arc::new(OutputHub::new(false, 2));
+        let receiver = hub.subscribe();
+        let calls = Arc::new(Mutex::new(0));
+        let counted = Arc::clone(&calls);
+        hub.on_output(|_| panic!("subscriber bug"));
+        hub.on_output(move |_| *counted.lock().unwrap() += 1);
+
+        let input = Cursor::new("one\ntwo\r\nthree\n");
+        hub.attach(input, OutputStream::Stdout).join().unwrap();
+
+        assert_eq!(*calls.lock().unwrap(), 3);
+        let lines: Vec<String> = receiver.try_iter().map(|line| line.line).collect();
+        assert_eq!(lines, ["one", "two", "three"]);
+        let tail: Vec<String> = hub.tail().into_iter().map(|line| line.line).collect();
+        assert_eq!(tail, ["two", "three"]);
+        assert_eq!(hub.lock().subscribers.len(), 2);
+    }
+}
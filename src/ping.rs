@@ -0,0 +1,455 @@
+use std::io::{self, Read, Write};
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::time::{Duration, Instant};
+
+use serde_json::Value;
+
+use crate::TextComponent;
+
+pub const DEFAULT_SERVER_PORT: u16 = 25565;
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+// Any version works for a status request, the server answers with its own protocol number.
+const HANDSHAKE_PROTOCOL: i32 = -1;
+const LEGACY_PROTOCOL: u8 = 74;
+const MAX_PACKET_LENGTH: usize = 2 * 1024 * 1024;
+
+#[derive(Debug, thiserror::Error)]
+pub enum PingError {
+    #[error("invalid status response: {0}")]
+    InvalidResponse(String),
+    #[error("status ping failed: {0}")]
+    Io(#[from] io::Error),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayerSample {
+    pub name: String,
+    pub id: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerStatus {
+    pub version: String,
+    pub protocol: Option<i32>,
+    pub players_online: u32,
+    pub players_max: u32,
+    pub sample: Vec<PlayerSample>,
+    pub description: TextComponent,
+    pub favicon: Option<String>,
+    pub enforces_secure_chat: Option<bool>,
+    pub latency: Option<Duration>,
+    pub legacy: bool,
+}
+
+impl ServerStatus {
+    pub fn parse(json: &str) -> Result<ServerStatus, PingError> {
+        let json: Value = serde_json::from_str(json).map_err(|e| PingError::InvalidResponse(e.to_string()))?;
+        let missing = |field: &str| PingError::InvalidResponse(format!("missing {}", field));
+
+        let version = json.get("version").ok_or_else(|| missing("version"))?;
+        let players = json.get("players");
+        let count = |field: &str| {
+            players
+                .and_then(|players| players.get(field)?.as_u64())
+                .map_or(0, |count| count.min(u32::MAX as u64) as u32)
+        };
+        let sample = players
+            .and_then(|players| players.get("sample")?.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|player| {
+                Some(PlayerSample {
+                    name: player.get("name")?.as_str()?.to_string(),
+                    id: player.get("id")?.as_str()?.to_string(),
+                })
+            })
+            .collect();
+
+        Ok(ServerStatus {
+            version: version.get("name").and_then(Value::as_str).unwrap_or_default().to_string(),
+            protocol: version.get("protocol").and_then(Value::as_i64).map(|protocol| protocol as i32),
+            players_online: count("online"),
+            players_max: count("max"),
+            sample,
+            description: json.get("description").map_or_else(|| TextComponent::text(""), TextComponent::from_json),
+            favicon: json.get("favicon").and_then(Value::as_str).map(str::to_string),
+            enforces_secure_chat: json.get("enforcesSecureChat").and_then(Value::as_bool),
+            latency: None,
+            legacy: false,
+        })
+    }
+
+    // Legacy responses are either `§1\0protocol\0version\0motd\0online\0max` (1.4 - 1.6)
+    // or `motd§online§max` from older servers.
+    fn parse_legacy(response: &str) -> Result<ServerStatus, PingError> {
+        let invalid = || PingError::InvalidResponse(response.to_string());
+        let count = |value: &str| value.parse::<u32>().map_err(|_| invalid());
+
+        let (protocol, version, motd, online, max) = if let Some(fields) = response.strip_prefix("\u{a7}1\0") {
+            let fields: Vec<&str> = fields.split('\0').collect();
+            let [protocol, version, motd, online, max] = fields[..] else {
+                return Err(invalid());
+            };
+            (protocol.parse().ok(), version, motd, online, max)
+        } else {
+            let mut fields = response.rsplitn(3, '\u{a7}');
+            let max = fields.next().ok_or_else(invalid)?;
+            let online = fields.next().ok_or_else(invalid)?;
+            let motd = fields.next().ok_or_else(invalid)?;
+            (None, "", motd, online, max)
+        };
+
+        Ok(ServerStatus {
+            version: version.to_string(),
+            protocol,
+            players_online: count(online)?,
+            players_max: count(max)?,
+            sample: Vec::new(),
+            description: TextComponent::from_legacy(motd),
+            favicon: None,
+            enforces_secure_chat: None,
+            latency: None,
+            legacy: true,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ServerPing {
+    host: String,
+    port: u16,
+    timeout: Duration,
+}
+
+impl ServerPing {
+    pub fn new<T: Into<String>>(host: T, port: u16) -> Self {
+        ServerPing {
+            host: host.into(),
+            port,
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    // Falls back to the legacy ping when the server does not speak the 1.7+ protocol,
+    // but not when it could not be reached at all.
+    pub fn status(&self) -> Result<ServerStatus, PingError> {
+        let addr = self.resolve()?;
+        match self.modern(addr) {
+            Ok(status) => Ok(status),
+            Err(PingError::Io(e)) if e.kind() == io::ErrorKind::ConnectionRefused => Err(PingError::Io(e)),
+            Err(e) => self.legacy(addr).map_err(|_| e),
+        }
+    }
+
+    pub fn modern_status(&self) -> Result<ServerStatus, PingError> {
+        self.modern(self.resolve()?)
+    }
+
+    pub fn legacy_status(&self) -> Result<ServerStatus, PingError> {
+        self.legacy(self.resolve()?)
+    }
+
+    fn resolve(&self) -> Result<SocketAddr, PingError> {
+        (self.host.as_str(), self.port)
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| PingError::Io(io::Error::new(io::ErrorKind::NotFound, format!("could not resolve {}", self.host))))
+    }
+
+    fn connect(&self, addr: SocketAddr) -> io::Result<TcpStream> {
+        let stream = TcpStream::connect_timeout(&addr, self.timeout)?;
+        stream.set_read_timeout(Some(self.timeout))?;
+        stream.set_write_timeout(Some(self.timeout))?;
+        stream.set_nodelay(true)?;
+        Ok(stream)
+    }
+
+    fn modern(&self, addr: SocketAddr) -> Result<ServerStatus, PingError> {
+        let mut stream = self.connect(addr)?;
+
+        let mut handshake = Vec::new();
+        write_varint(&mut handshake, 0x00);
+        write_varint(&mut handshake, HANDSHAKE_PROTOCOL);
+        write_string(&mut handshake, &self.host);
+        handshake.extend_from_slice(&self.port.to_be_bytes());
+        write_varint(&mut handshake, 1);
+        write_packet(&mut stream, &handshake)?;
+        write_packet(&mut stream, &[0x00])?;
+
+        let response = read_packet(&mut stream)?;
+        let mut cursor = response.as_slice();
+        if read_varint(&mut cursor)? != 0x00 {
+            return Err(PingError::InvalidResponse("unexpected packet id".to_string()));
+        }
+        let length = read_varint(&mut cursor)?;
+        let json = usize::try_from(length)
+            .ok()
+            .and_then(|length| cursor.get(..length))
+            .ok_or_else(|| PingError::InvalidResponse("truncated status".to_string()))?;
+        let mut status = ServerStatus::parse(&String::from_utf8_lossy(json))?;
+
+        // Some servers close the connection right after the status, the latency is optional.
+        let payload = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |now| now.as_millis() as i64);
+        let mut ping = vec![0x01];
+        ping.extend_from_slice(&payload.to_be_bytes());
+        let sent = Instant::now();
+        if write_packet(&mut stream, &ping).is_ok()
+            && let Ok(pong) = read_packet(&mut stream)
+            && pong == ping
+        {
+            status.latency = Some(sent.elapsed());
+        }
+        Ok(status)
+    }
+
+    fn legacy(&self, addr: SocketAddr) -> Result<ServerStatus, PingError> {
+        // 1.6 clients append an MC|PingHost plugin message, older servers stop reading after 0xFE 0x01.
+        let mut stream = self.connect(addr)?;
+        let mut request = vec![0xFE, 0x01, 0xFA];
+        write_utf16(&mut request, "MC|PingHost");
+        let host: Vec<u16> = self.host.encode_utf16().collect();
+        request.extend_from_slice(&(7 + 2 * host.len() as u16).to_be_bytes());
+        request.push(LEGACY_PROTOCOL);
+        write_utf16(&mut request, &self.host);
+        request.extend_from_slice(&(self.port as i32).to_be_bytes());
+        stream.write_all(&request)?;
+
+        let sent = Instant::now();
+        let response = match read_legacy_response(&mut stream) {
+            Ok(response) => response,
+            // Pre-1.4 servers may drop the connection on the extra bytes, ask again with a bare 0xFE.
+            Err(PingError::Io(_)) => {
+                let mut stream = self.connect(addr)?;
+                stream.write_all(&[0xFE])?;
+                read_legacy_response(&mut stream)?
+            }
+            Err(e) => return Err(e),
+        };
+        let mut status = ServerStatus::parse_legacy(&response)?;
+        status.latency = Some(sent.elapsed());
+        Ok(status)
+    }
+}
+
+fn read_legacy_response(stream: &mut TcpStream) -> Result<String, PingError> {
+    let mut header = [0; 3];
+    stream.read_exact(&mut header)?;
+    if header[0] != 0xFF {
+        return Err(PingError::InvalidResponse(format!("unexpected packet id {:#04x}", header[0])));
+    }
+    let length = u16::from_be_bytes([header[1], header[2]]) as usize;
+    let mut bytes = vec![0; length * 2];
+    stream.read_exact(&mut bytes)?;
+    let units: Vec<u16> = bytes.chunks_exact(2).map(|unit| u16::from_be_bytes([unit[0], unit[1]])).collect();
+    Ok(String::from_utf16_lossy(&units))
+}
+
+fn write_utf16(buf: &mut Vec<u8>, value: &str) {
+    let units: Vec<u16> = value.encode_utf16().collect();
+    buf.extend_from_slice(&(units.len() as u16).to_be_bytes());
+    for unit in units {
+        buf.extend_from_slice(&unit.to_be_bytes());
+    }
+}
+
+fn write_varint(buf: &mut Vec<u8>, value: i32) {
+    let mut value = value as u32;
+    loop {
+        if value & !0x7F == 0 {
+            buf.push(value as u8);
+            return;
+        }
+        buf.push((value & 0x7F) as u8 | 0x80);
+        value >>= 7;
+    }
+}
+
+fn read_varint<R: Read>(reader: &mut R) -> Result<i32, PingError> {
+    let mut value = 0u32;
+    for position in 0..5 {
+        let mut byte = [0];
+        reader.read_exact(&mut byte)?;
+        value |= ((byte[0] & 0x7F) as u32) << (7 * position);
+        if byte[0] & 0x80 == 0 {
+            return Ok(value as i32);
+        }
+    }
+    Err(PingError::InvalidResponse("VarInt is too long".to_string()))
+}
+
+fn write_string(buf: &mut Vec<u8>, value: &str) {
+    write_varint(buf, value.len() as i32);
+    buf.extend_from_slice(value.as_bytes());
+}
+
+fn write_packet<W: Write>(writer: &mut W, packet: &[u8]) -> io::Result<()> {
+    let mut framed = Vec::with_capacity(packet.len() + 5);
+    write_varint(&mut framed, packet.len() as i32);
+    framed.extend_from_slice(packet);
+    writer.write_all(&framed)
+}
+
+fn read_packet<R: Read>(reader: &mut R) -> Result<Vec<u8>, PingError> {
+    let length = read_varint(reader)?;
+    let length = usize::try_from(length)
+        .ok()
+        .filter(|length| (1..=MAX_PACKET_LENGTH).contains(length))
+        .ok_or_else(|| PingError::InvalidResponse(format!("packet length {}", length)))?;
+    let mut packet = vec![0; length];
+    reader.read_exact(&mut packet)?;
+    Ok(packet)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::TcpListener;
+    use std::thread;
+
+    use super::*;
+
+    fn fake_server<F: FnMut(TcpStream) + Send + 'static>(connections: usize, mut handler: F) -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            for _ in 0..connections {
+                let (stream, _) = listener.accept().unwrap();
+                handler(stream);
+            }
+        });
+        addr
+    }
+
+    fn read_status_request(stream: &mut TcpStream) {
+        let handshake = read_packet(stream).unwrap();
+        let mut cursor = handshake.as_slice();
+        assert_eq!(read_varint(&mut cursor).unwrap(), 0x00);
+        assert_eq!(read_varint(&mut cursor).unwrap(), HANDSHAKE_PROTOCOL);
+        let host_length = read_varint(&mut cursor).unwrap() as usize;
+        assert_eq!(&cursor[..host_length], b"127.0.0.1");
+        assert_eq!(cursor[host_length + 2..], [1]);
+        assert_eq!(read_packet(stream).unwrap(), [0x00]);
+    }
+
+    // Reads the whole 1.6 request, closing with unread bytes would reset the connection.
+    fn read_legacy_request(stream: &mut TcpStream) {
+        let mut header = [0; 3];
+        stream.read_exact(&mut header).unwrap();
+        assert_eq!(header, [0xFE, 0x01, 0xFA]);
+        for _ in 0..2 {
+            let mut length = [0; 2];
+            stream.read_exact(&mut length).unwrap();
+            let mut length = u16::from_be_bytes(length) as usize;
+            if header[2] == 0xFA {
+                length *= 2;
+                header[2] = 0;
+            }
+            stream.read_exact(&mut vec![0; length]).unwrap();
+        }
+    }
+
+    fn write_legacy_response(stream: &mut TcpStream, response: &str) {
+        let mut packet = vec![0xFF];
+        write_utf16(&mut packet, response);
+        stream.write_all(&packet).unwrap();
+    }
+
+    fn ping(addr: SocketAddr) -> ServerPing {
+        ServerPing::new("127.0.0.1", addr.port()).timeout(Duration::from_secs(2))
+    }
+
+    #[test]
+    fn reads_a_modern_status() {
+        let addr = fake_server(1, |mut stream| {
+            read_status_request(&mut stream);
+            let mut response = Vec::new();
+            write_varint(&mut response, 0x00);
+            write_string(
+                &mut response,
+                r#"{"version":{"name":"1.21.4","protocol":769},"players":{"online":1,"max":20,"sample":[{"name":"Steve","id":"8667ba71-b85a-4004-af54-457a9734eed7"}]},"description":{"text":"A Minecraft Server"},"enforcesSecureChat":true}"#,
+            );
+            write_packet(&mut stream, &response).unwrap();
+            let ping = read_packet(&mut stream).unwrap();
+            write_packet(&mut stream, &ping).unwrap();
+        });
+
+        let status = ping(addr).modern_status().unwrap();
+        assert_eq!(status.version, "1.21.4");
+        assert_eq!(status.protocol, Some(769));
+        assert_eq!((status.players_online, status.players_max), (1, 20));
+        assert_eq!(status.sample[0].name, "Steve");
+        assert_eq!(status.description.to_plain(), "A Minecraft Server");
+        assert_eq!(status.enforces_secure_chat, Some(true));
+        assert!(status.latency.is_some());
+        assert!(!status.legacy);
+    }
+
+    #[test]
+    fn reads_a_1_6_legacy_status() {
+        let addr = fake_server(1, |mut stream| {
+            read_legacy_request(&mut stream);
+            write_legacy_response(&mut stream, &["\u{a7}1", "74", "1.6.4", "A \u{a7}aLegacy\u{a7}r Server", "3", "20"].join("\0"));
+        });
+
+        let status = ping(addr).legacy_status().unwrap();
+        assert_eq!(status.version, "1.6.4");
+        assert_eq!(status.protocol, Some(74));
+        assert_eq!((status.players_online, status.players_max), (3, 20));
+        assert_eq!(status.description.to_plain(), "A Legacy Server");
+        assert!(status.legacy);
+    }
+
+    #[test]
+    fn retries_pre_1_4_servers_with_a_bare_ping() {
+        let mut connection = 0;
+        let addr = fake_server(2, move |mut stream| {
+            connection += 1;
+            if connection == 1 {
+                read_legacy_request(&mut stream);
+                return;
+            }
+            let mut request = [0];
+            stream.read_exact(&mut request).unwrap();
+            assert_eq!(request, [0xFE]);
+            write_legacy_response(&mut stream, "A Beta Server\u{a7}0\u{a7}8");
+        });
+
+        let status = ping(addr).legacy_status().unwrap();
+        assert_eq!(status.version, "");
+        assert_eq!(status.protocol, None);
+        assert_eq!((status.players_online, status.players_max), (0, 8));
+        assert_eq!(status.description.to_plain(), "A Beta Server");
+    }
+
+    #[test]
+    fn falls_back_to_legacy_when_the_modern_ping_fails() {
+        let mut connection = 0;
+        let addr = fake_server(2, move |mut stream| {
+            connection += 1;
+            if connection == 1 {
+                read_status_request(&mut stream);
+                return;
+            }
+            read_legacy_request(&mut stream);
+            write_legacy_response(&mut stream, &["\u{a7}1", "74", "1.6.4", "A Server", "0", "20"].join("\0"));
+        });
+
+        let status = ping(addr).status().unwrap();
+        assert!(status.legacy);
+        assert_eq!(status.version, "1.6.4");
+    }
+
+    #[test]
+    fn does_not_fall_back_when_the_connection_is_refused() {
+        let addr = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        let result = ping(addr).status();
+        assert!(matches!(result, Err(PingError::Io(e)) if e.kind() == io::ErrorKind::ConnectionRefused));
+    }
+}
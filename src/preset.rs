@@ -0,0 +1,99 @@
+use crate::{ByteSize, JavaVersion};
+
+const LARGE_HEAP: ByteSize = ByteSize::gibibytes(12);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JvmPreset {
+    Aikar,
+    ZgcGenerational,
+    Shenandoah,
+    Minimal,
+}
+
+impl JvmPreset {
+    pub fn flags(&self, min_memory: Option<ByteSize>, max_memory: Option<ByteSize>, java: Option<&JavaVersion>) -> Vec<String> {
+        let major = java.map(|java| java.major);
+        let mut flags: Vec<&str> = Vec::new();
+
+        match self {
+            JvmPreset::Aikar => return aikar_flags(min_memory, max_memory, major),
+            JvmPreset::ZgcGenerational => match major {
+                Some(21..=22) => flags.extend(["-XX:+UseZGC", "-XX:+ZGenerational"]),
+                Some(15..) => flags.push("-XX:+UseZGC"),
+                _ => return aikar_flags(min_memory, max_memory, major),
+            },
+            JvmPreset::Shenandoah => {
+                // Oracle builds ship without Shenandoah, everyone else has it since JDK 12.
+                let oracle = java
+                    .and_then(|java| java.vendor.as_deref())
+                    .is_some_and(|vendor| vendor.starts_with("Oracle"));
+                if oracle || major.is_none_or(|major| major < 12) {
+                    return aikar_flags(min_memory, max_memory, major);
+                }
+                flags.extend(["-XX:+UseShenandoahGC", "-XX:+ParallelRefProcEnabled"]);
+            }
+            JvmPreset::Minimal => {
+                if major.is_none_or(|major| major < 9) {
+                    flags.push("-XX:+UseG1GC");
+                }
+                flags.push("-XX:+DisableExplicitGC");
+                return flags.into_iter().map(str::to_string).collect();
+            }
+        }
+
+        flags.push("-XX:+DisableExplicitGC");
+        if pre_touch(min_memory, max_memory) {
+            flags.push("-XX:+AlwaysPreTouch");
+        }
+        flags.push("-XX:+PerfDisableSharedMem");
+        flags.into_iter().map(str::to_string).collect()
+    }
+}
+
+// See https://docs.papermc.io/paper/aikars-flags
+fn aikar_flags(min_memory: Option<ByteSize>, max_memory: Option<ByteSize>, major: Option<u32>) -> Vec<String> {
+    let large = max_memory.is_some_and(|max| max >= LARGE_HEAP);
+    let (new_size, max_new_size, region_size, reserve, occupancy) = if large {
+        (40, 50, "16M", 15, 20)
+    } else {
+        (30, 40, "8M", 20, 15)
+    };
+
+    let mut flags = vec![
+        "-XX:+UseG1GC".to_string(),
+        "-XX:+ParallelRefProcEnabled".to_string(),
+        "-XX:MaxGCPauseMillis=200".to_string(),
+        "-XX:+UnlockExperimentalVMOptions".to_string(),
+        "-XX:+DisableExplicitGC".to_string(),
+    ];
+    if pre_touch(min_memory, max_memory) {
+        flags.push("-XX:+AlwaysPreTouch".to_string());
+    }
+    flags.extend([
+        format!("-XX:G1NewSizePercent={}", new_size),
+        format!("-XX:G1MaxNewSizePercent={}", max_new_size),
+        format!("-XX:G1HeapRegionSize={}", region_size),
+        format!("-XX:G1ReservePercent={}", reserve),
+        "-XX:G1HeapWastePercent=5".to_string(),
+        "-XX:G1MixedGCCountTarget=4".to_string(),
+        format!("-XX:InitiatingHeapOccupancyPercent={}", occupancy),
+        "-XX:G1MixedGCLiveThresholdPercent=90".to_string(),
+    ]);
+    // Obsoleted by the JDK 20 remembered set rework, newer JVMs warn about it on startup.
+    if major.is_some_and(|major| major < 20) {
+        flags.push("-XX:G1RSetUpdatingPauseTimePercent=5".to_string());
+    }
+    flags.extend([
+        "-XX:SurvivorRatio=32".to_string(),
+        "-XX:+PerfDisableSharedMem".to_string(),
+        "-XX:MaxTenuringThreshold=1".to_string(),
+        "-Dusing.aikars.flags=https://mcflags.emc.gs".to_string(),
+        "-Daikars.new.flags=true".to_string(),
+    ]);
+    flags
+}
+
+// Pre-touching only pays off when the heap is committed up front.
+fn pre_touch(min_memory: Option<ByteSize>, max_memory: Option<ByteSize>) -> bool {
+    min_memory.is_some() && min_memory == max_memory
+}
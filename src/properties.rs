@@ -0,0 +1,477 @@
+use std::fmt::{self, Display, Write as _};
+use std::path::Path;
+use std::str::FromStr;
+
+pub const SERVER_PROPERTIES_FILE: &str = "server.properties";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Entry {
+    Raw(String),
+    Property {
+        key: String,
+        value: String,
+        raw: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ServerProperties {
+    entries: Vec<Entry>,
+}
+
+impl ServerProperties {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, std::io::Error> {
+        let bytes = std::fs::read(path)?;
+        let text = match String::from_utf8(bytes) {
+            Ok(text) => text,
+            Err(e) => e.into_bytes().iter().map(|&b| b as char).collect(),
+        };
+        Ok(Self::parse(&text))
+    }
+
+    pub fn load_from_dir<P: AsRef<Path>>(server_path: P) -> Result<Self, std::io::Error> {
+        Self::load(server_path.as_ref().join(SERVER_PROPERTIES_FILE))
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), std::io::Error> {
+        std::fs::write(path, self.to_string())
+    }
+
+    pub fn save_to_dir<P: AsRef<Path>>(&self, server_path: P) -> Result<(), std::io::Error> {
+        self.save(server_path.as_ref().join(SERVER_PROPERTIES_FILE))
+    }
+
+    pub fn parse(text: &str) -> Self {
+        let mut entries = Vec::new();
+        let mut lines = text.lines();
+        while let Some(line) = lines.next() {
+            let trimmed = line.trim_start_matches([' ', '\t', '\x0c']);
+            if trimmed.is_empty() || trimmed.starts_with(['#', '!']) {
+                entries.push(Entry::Raw(line.to_string()));
+                continue;
+            }
+
+            let mut raw = line.to_string();
+            let mut logical = trimmed.to_string();
+            while ends_with_continuation(&logical) {
+                logical.pop();
+                match lines.next() {
+                    Some(next) => {
+                        raw.push('\n');
+                        raw.push_str(next);
+                        logical.push_str(next.trim_start_matches([' ', '\t', '\x0c']));
+                    }
+                    None => break,
+                }
+            }
+
+            let (key, value) = split_key_value(&logical);
+            entries.push(Entry::Property {
+                key: unescape(key),
+                value: unescape(value),
+                raw: Some(raw),
+            });
+        }
+        ServerProperties { entries }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries.iter().rev().find_map(|entry| match entry {
+            Entry::Property { key: k, value, .. } if k == key => Some(value.as_str()),
+            _ => None,
+        })
+    }
+
+    pub fn set<K: Into<String>, V: Into<String>>(&mut self, key: K, value: V) {
+        let key = key.into();
+        let value = value.into();
+        let existing = self.entries.iter_mut().rev().find_map(|entry| match entry {
+            Entry::Property { key: k, value, raw } if *k == key => Some((value, raw)),
+            _ => None,
+        });
+        match existing {
+            Some((old, raw)) => {
+                if *old != value {
+                    *old = value;
+                    *raw = None;
+                }
+            }
+            None => self.entries.push(Entry::Property { key, value, raw: None }),
+        }
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<String> {
+        let mut removed = None;
+        self.entries.retain(|entry| match entry {
+            Entry::Property { key: k, value, .. } if k == key => {
+                removed = Some(value.clone());
+                false
+            }
+            _ => true,
+        });
+        removed
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.entries.iter().filter_map(|entry| match entry {
+            Entry::Property { key, value, .. } => Some((key.as_str(), value.as_str())),
+            Entry::Raw(_) => None,
+        })
+    }
+
+    pub fn get_parsed<T: FromStr>(&self, key: &str) -> Option<T> {
+        self.get(key)?.trim().parse().ok()
+    }
+
+    pub fn server_ip(&self) -> Option<&str> {
+        self.get("server-ip").filter(|ip| !ip.is_empty())
+    }
+
+    pub fn server_port(&self) -> Option<u16> {
+        self.get_parsed("server-port")
+    }
+
+    pub fn set_server_port(&mut self, port: u16) {
+        self.set("server-port", port.to_string());
+    }
+
+    pub fn motd(&self) -> Option<&str> {
+        self.get("motd")
+    }
+
+    pub fn set_motd<T: Into<String>>(&mut self, motd: T) {
+        self.set("motd", motd);
+    }
+
+    pub fn max_players(&self) -> Option<u32> {
+        self.get_parsed("max-players")
+    }
+
+    pub fn set_max_players(&mut self, max_players: u32) {
+        self.set("max-players", max_players.to_string());
+    }
+
+    pub fn online_mode(&self) -> Option<bool> {
+        self.get_parsed("online-mode")
+    }
+
+    pub fn set_online_mode(&mut self, online_mode: bool) {
+        self.set("online-mode", online_mode.to_string());
+    }
+
+    pub fn level_name(&self) -> Option<&str> {
+        self.get("level-name")
+    }
+
+    pub fn set_level_name<T: Into<String>>(&mut self, level_name: T) {
+        self.set("level-name", level_name);
+    }
+
+    pub fn difficulty(&self) -> Option<Difficulty> {
+        self.get_parsed("difficulty")
+    }
+
+    pub fn set_difficulty(&mut self, difficulty: Difficulty) {
+        self.set("difficulty", difficulty.to_string());
+    }
+
+    pub fn gamemode(&self) -> Option<GameMode> {
+        self.get_parsed("gamemode")
+    }
+
+    pub fn set_gamemode(&mut self, gamemode: GameMode) {
+        self.set("gamemode", gamemode.to_string());
+    }
+
+    pub fn enable_rcon(&self) -> Option<bool> {
+        self.get_parsed("enable-rcon")
+    }
+
+    pub fn set_enable_rcon(&mut self, enable: bool) {
+        self.set("enable-rcon", enable.to_string());
+    }
+
+    pub fn rcon_port(&self) -> Option<u16> {
+        self.get_parsed("rcon.port")
+    }
+
+    pub fn set_rcon_port(&mut self, port: u16) {
+        self.set("rcon.port", port.to_string());
+    }
+
+    pub fn rcon_password(&self) -> Option<&str> {
+        self.get("rcon.password").filter(|password| !password.is_empty())
+    }
+
+    pub fn set_rcon_password<T: Into<String>>(&mut self, password: T) {
+        self.set("rcon.password", password);
+    }
+
+    pub fn enable_query(&self) -> Option<bool> {
+        self.get_parsed("enable-query")
+    }
+
+    pub fn set_enable_query(&mut self, enable: bool) {
+        self.set("enable-query", enable.to_string());
+    }
+
+    pub fn query_port(&self) -> Option<u16> {
+        self.get_parsed("query.port")
+    }
+
+    pub fn set_query_port(&mut self, port: u16) {
+        self.set("query.port", port.to_string());
+    }
+}
+
+impl FromStr for ServerProperties {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::parse(s))
+    }
+}
+
+impl Display for ServerProperties {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for entry in &self.entries {
+            match entry {
+                Entry::Raw(line) => writeln!(f, "{}", line)?,
+                Entry::Property { raw: Some(raw), .. } => writeln!(f, "{}", raw)?,
+                Entry::Property { key, value, raw: None } => {
+                    writeln!(f, "{}={}", escape(key, true), escape(value, false))?
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Difficulty {
+    Peaceful,
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "peaceful" | "0" => Ok(Difficulty::Peaceful),
+            "easy" | "1" => Ok(Difficulty::Easy),
+            "normal" | "2" => Ok(Difficulty::Normal),
+            "hard" | "3" => Ok(Difficulty::Hard),
+            _ => Err(format!("invalid difficulty: {}", s)),
+        }
+    }
+}
+
+impl Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Difficulty::Peaceful => "peaceful",
+            Difficulty::Easy => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameMode {
+    Survival,
+    Creative,
+    Adventure,
+    Spectator,
+}
+
+impl FromStr for GameMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "survival" | "0" => Ok(GameMode::Survival),
+            "creative" | "1" => Ok(GameMode::Creative),
+            "adventure" | "2" => Ok(GameMode::Adventure),
+            "spectator" | "3" => Ok(GameMode::Spectator),
+            _ => Err(format!("invalid game mode: {}", s)),
+        }
+    }
+}
+
+impl Display for GameMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            GameMode::Survival => "survival",
+            GameMode::Creative => "creative",
+            GameMode::Adventure => "adventure",
+            GameMode::Spectator => "spectator",
+        })
+    }
+}
+
+fn ends_with_continuation(line: &str) -> bool {
+    line.bytes().rev().take_while(|&b| b == b'\\').count() % 2 == 1
+}
+
+fn split_key_value(line: &str) -> (&str, &str) {
+    let mut escaped = false;
+    let mut key_end = line.len();
+    for (i, c) in line.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if matches!(c, '=' | ':' | ' ' | '\t' | '\x0c') {
+            key_end = i;
+            break;
+        }
+    }
+
+    let rest = line[key_end..].trim_start_matches([' ', '\t', '\x0c']);
+    let rest = rest.strip_prefix(['=', ':']).unwrap_or(rest);
+    (&line[..key_end], rest.trim_start_matches([' ', '\t', '\x0c']))
+}
+
+fn unescape(s: &str) -> String {
+    let mut units: Vec<u16> = Vec::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        let c = if c == '\\' {
+            match chars.next() {
+                Some('t') => '\t',
+                Some('n') => '\n',
+                Some('r') => '\r',
+                Some('f') => '\x0c',
+                Some('u') => {
+                    let hex: String = chars.clone().take(4).collect();
+                    match u16::from_str_radix(&hex, 16) {
+                        Ok(unit) if hex.len() == 4 => {
+                            chars.nth(3);
+                            units.push(unit);
+                            continue;
+                        }
+                        _ => 'u',
+                    }
+                }
+                Some(other) => other,
+                None => break,
+            }
+        } else {
+            c
+        };
+        let mut buf = [0; 2];
+        units.extend_from_slice(c.encode_utf16(&mut buf));
+    }
+    String::from_utf16_lossy(&units)
+}
+
+fn escape(s: &str, is_key: bool) -> String {
+    let mut out = String::with_capacity(s.len());
+    for (i, c) in s.chars().enumerate() {
+        match c {
+            ' ' if is_key || i == 0 => out.push_str("\\ "),
+            '\\' => out.push_str("\\\\"),
+            '\t' => out.push_str("\\t"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\x0c' => out.push_str("\\f"),
+            '=' | ':' | '#' | '!' => {
+                out.push('\\');
+                out.push(c);
+            }
+            ' '..='~' => out.push(c),
+            _ => {
+                let mut buf = [0; 2];
+                for unit in c.encode_utf16(&mut buf) {
+                    let _ = write!(out, "\\u{:04X}", unit);
+                }
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(key: &str, value: &str) {
+        let mut properties = ServerProperties::new();
+        properties.set(key, value);
+        let text = properties.to_string();
+        assert_eq!(ServerProperties::parse(&text).get(key), Some(value), "{:?}", text);
+    }
+
+    #[test]
+    fn decodes_surrogate_pairs() {
+        let properties = ServerProperties::parse("motd=\\u00A7aHello \\uD83D\\uDE00\n");
+        assert_eq!(properties.motd(), Some("\u{a7}aHello \u{1F600}"));
+    }
+
+    #[test]
+    fn encodes_non_ascii_as_utf16_escapes() {
+        let mut properties = ServerProperties::new();
+        properties.set_motd("\u{a7}a\u{1F600}");
+        assert_eq!(properties.to_string(), "motd=\\u00A7a\\uD83D\\uDE00\n");
+        round_trip("motd", "\u{a7}a\u{1F600} caf\u{e9}");
+    }
+
+    #[test]
+    fn handles_escaped_separators_in_keys() {
+        let properties = ServerProperties::parse("my\\:odd\\ key\\=name = value: with = signs\n");
+        assert_eq!(properties.get("my:odd key=name"), Some("value: with = signs"));
+        round_trip("my:odd key=name", "value: with = signs");
+    }
+
+    #[test]
+    fn keeps_leading_spaces_in_values() {
+        let properties = ServerProperties::parse("motd=\\  indented\n");
+        assert_eq!(properties.motd(), Some("  indented"));
+        round_trip("motd", "  indented");
+        round_trip("motd", "");
+    }
+
+    #[test]
+    fn joins_continuation_lines() {
+        let text = "motd=Hello \\\n    World\\\n\tagain\nmax-players=20\n";
+        let properties = ServerProperties::parse(text);
+        assert_eq!(properties.motd(), Some("Hello Worldagain"));
+        assert_eq!(properties.max_players(), Some(20));
+        assert_eq!(properties.to_string(), text);
+    }
+
+    #[test]
+    fn an_escaped_backslash_is_not_a_continuation() {
+        let properties = ServerProperties::parse("level-name=C\\:\\\\worlds\\\\\nmax-players=20\n");
+        assert_eq!(properties.level_name(), Some("C:\\worlds\\"));
+        assert_eq!(properties.max_players(), Some(20));
+        round_trip("level-name", "C:\\worlds\\");
+    }
+
+    #[test]
+    fn preserves_untouched_lines() {
+        let text = "#Minecraft server properties\n#Mon Jan 01 00:00:00 UTC 2024\n\nmotd : A  Minecraft\\u0020Server\n! legacy comment\nmax-players=20\n";
+        let mut properties = ServerProperties::parse(text);
+        assert_eq!(properties.to_string(), text);
+
+        properties.set_max_players(40);
+        properties.set_online_mode(false);
+        assert_eq!(
+            properties.to_string(),
+            text.replace("max-players=20", "max-players=40") + "online-mode=false\n"
+        );
+    }
+
+    #[test]
+    fn escapes_control_characters() {
+        round_trip("motd", "line one\nline\ttwo\r\x0c#!");
+    }
+}
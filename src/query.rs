@@ -0,0 +1,391 @@
+use std::io;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs, UdpSocket};
+use std::time::Duration;
+
+use crate::ServerProperties;
+use crate::ping::DEFAULT_SERVER_PORT;
+
+const MAGIC: [u8; 2] = [0xFE, 0xFD];
+const TYPE_HANDSHAKE: u8 = 9;
+const TYPE_STAT: u8 = 0;
+// The server only looks at the low nibble of each byte of the session id.
+const SESSION_ID_MASK: i32 = 0x0F0F_0F0F;
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+const MAX_PACKET_LENGTH: usize = 65535;
+
+#[derive(Debug, thiserror::Error)]
+pub enum QueryError {
+    #[error("failed to read server.properties: {0}")]
+    Properties(io::Error),
+    #[error("query is disabled in server.properties")]
+    Disabled,
+    #[error("invalid query response: {0}")]
+    InvalidResponse(String),
+    #[error("query failed: {0}")]
+    Io(#[from] io::Error),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BasicStat {
+    pub motd: String,
+    pub game_type: String,
+    pub map: String,
+    pub players_online: u32,
+    pub players_max: u32,
+    pub host_port: u16,
+    pub host_ip: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FullStat {
+    pub motd: String,
+    pub game_type: String,
+    pub game_id: String,
+    pub version: String,
+    pub server_mod: Option<String>,
+    pub plugins: Vec<String>,
+    pub map: String,
+    pub players_online: u32,
+    pub players_max: u32,
+    pub host_port: u16,
+    pub host_ip: String,
+    pub players: Vec<String>,
+}
+
+pub struct QueryClient {
+    socket: UdpSocket,
+    session_id: i32,
+}
+
+impl QueryClient {
+    pub fn connect<A: ToSocketAddrs>(addr: A) -> Result<Self, QueryError> {
+        let addr = addr
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "could not resolve query address"))?;
+        let local: SocketAddr = if addr.is_ipv4() {
+            (Ipv4Addr::UNSPECIFIED, 0).into()
+        } else {
+            (Ipv6Addr::UNSPECIFIED, 0).into()
+        };
+        let socket = UdpSocket::bind(local)?;
+        socket.connect(addr)?;
+        socket.set_read_timeout(Some(DEFAULT_TIMEOUT))?;
+        socket.set_write_timeout(Some(DEFAULT_TIMEOUT))?;
+        Ok(QueryClient {
+            socket,
+            session_id: std::process::id() as i32 & SESSION_ID_MASK,
+        })
+    }
+
+    pub fn from_properties(properties: &ServerProperties) -> Result<Self, QueryError> {
+        if properties.enable_query() != Some(true) {
+            return Err(QueryError::Disabled);
+        }
+        let host = properties.server_ip().unwrap_or("127.0.0.1");
+        let port = properties
+            .query_port()
+            .or_else(|| properties.server_port())
+            .unwrap_or(DEFAULT_SERVER_PORT);
+        Self::connect((host, port))
+    }
+
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) -> Result<(), QueryError> {
+        self.socket.set_read_timeout(timeout)?;
+        self.socket.set_write_timeout(timeout)?;
+        Ok(())
+    }
+
+    pub fn basic_stat(&mut self) -> Result<BasicStat, QueryError> {
+        let token = self.handshake()?;
+        let response = self.request(TYPE_STAT, &token.to_be_bytes())?;
+        let mut reader = Reader::new(&response);
+        let motd = reader.string()?;
+        let game_type = reader.string()?;
+        let map = reader.string()?;
+        let players_online = reader.number()?;
+        let players_max = reader.number()?;
+        let host_port = reader.u16_le()?;
+        let host_ip = reader.string()?;
+        Ok(BasicStat {
+            motd,
+            game_type,
+            map,
+            players_online,
+            players_max,
+            host_port,
+            host_ip,
+        })
+    }
+
+    pub fn full_stat(&mut self) -> Result<FullStat, QueryError> {
+        let token = self.handshake()?;
+        let mut payload = token.to_be_bytes().to_vec();
+        payload.extend_from_slice(&[0; 4]);
+        let response = self.request(TYPE_STAT, &payload)?;
+
+        // splitnum\0\x80\0, then key\0value\0 pairs up to an empty key, then \x01player_\0\0 and the names.
+        let mut reader = Reader::new(&response);
+        reader.skip(11)?;
+        let mut values = Vec::new();
+        loop {
+            let key = reader.string()?;
+            if key.is_empty() {
+                break;
+            }
+            values.push((key, reader.string()?));
+        }
+        reader.skip(10)?;
+        let mut players = Vec::new();
+        while let Ok(name) = reader.string() {
+            if name.is_empty() {
+                break;
+            }
+            players.push(name);
+        }
+
+        let value = |key: &str| {
+            values
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, v)| v.clone())
+                .unwrap_or_default()
+        };
+        let invalid = |key: &str| QueryError::InvalidResponse(format!("invalid {}", key));
+        let (server_mod, plugins) = parse_plugins(&value("plugins"));
+        Ok(FullStat {
+            motd: value("hostname"),
+            game_type: value("gametype"),
+            game_id: value("game_id"),
+            version: value("version"),
+            server_mod,
+            plugins,
+            map: value("map"),
+            players_online: value("numplayers").parse().map_err(|_| invalid("numplayers"))?,
+            players_max: value("maxplayers").parse().map_err(|_| invalid("maxplayers"))?,
+            host_port: value("hostport").parse().map_err(|_| invalid("hostport"))?,
+            host_ip: value("hostip"),
+            players,
+        })
+    }
+
+    // The challenge token is only valid for 30 seconds, so every stat request starts with a fresh one.
+    fn handshake(&mut self) -> Result<i32, QueryError> {
+        let response = self.request(TYPE_HANDSHAKE, &[])?;
+        let token = Reader::new(&response).string()?;
+        token
+            .parse()
+            .map_err(|_| QueryError::InvalidResponse(format!("challenge token {:?}", token)))
+    }
+
+    fn request(&mut self, kind: u8, payload: &[u8]) -> Result<Vec<u8>, QueryError> {
+        let mut packet = Vec::with_capacity(7 + payload.len());
+        packet.extend_from_slice(&MAGIC);
+        packet.push(kind);
+        packet.extend_from_slice(&self.session_id.to_be_bytes());
+        packet.extend_from_slice(payload);
+        self.socket.send(&packet)?;
+
+        let mut buf = vec![0; MAX_PACKET_LENGTH];
+        loop {
+            let length = self.socket.recv(&mut buf)?;
+            // Late answers to an earlier request carry another type or session id, skip them.
+            if length >= 5 && buf[0] == kind && buf[1..5] == self.session_id.to_be_bytes() {
+                return Ok(buf[5..length].to_vec());
+            }
+        }
+    }
+}
+
+// Bukkit style servers report `Paper on 1.21.4: WorldEdit 7.3.0; LuckPerms 5.4`, vanilla leaves it empty.
+fn parse_plugins(plugins: &str) -> (Option<String>, Vec<String>) {
+    let plugins = plugins.trim();
+    if plugins.is_empty() {
+        return (None, Vec::new());
+    }
+    match plugins.split_once(':') {
+        Some((server_mod, list)) => (
+            Some(server_mod.trim().to_string()),
+            list.split(';')
+                .map(str::trim)
+                .filter(|plugin| !plugin.is_empty())
+                .map(str::to_string)
+                .collect(),
+        ),
+        None => (Some(plugins.to_string()), Vec::new()),
+    }
+}
+
+struct Reader<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn new(buf: &'a [u8]) -> Self {
+        Reader { buf }
+    }
+
+    fn truncated() -> QueryError {
+        QueryError::InvalidResponse("truncated response".to_string())
+    }
+
+    fn skip(&mut self, count: usize) -> Result<(), QueryError> {
+        self.buf = self.buf.get(count..).ok_or_else(Self::truncated)?;
+        Ok(())
+    }
+
+    // Strings are null terminated and encoded as ISO-8859-1.
+    fn string(&mut self) -> Result<String, QueryError> {
+        let end = self.buf.iter().position(|&b| b == 0).ok_or_else(Self::truncated)?;
+        let value = self.buf[..end].iter().map(|&b| b as char).collect();
+        self.buf = &self.buf[end + 1..];
+        Ok(value)
+    }
+
+    fn number(&mut self) -> Result<u32, QueryError> {
+        let value = self.string()?;
+        value
+            .parse()
+            .map_err(|_| QueryError::InvalidResponse(format!("invalid number {:?}", value)))
+    }
+
+    fn u16_le(&mut self) -> Result<u16, QueryError> {
+        let bytes = self.buf.get(..2).ok_or_else(Self::truncated)?;
+        let value = u16::from_le_bytes([bytes[0], bytes[1]]);
+        self.buf = &self.buf[2..];
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+
+    const TOKEN: i32 = 9513307;
+
+    // Answers handshakes with TOKEN and stat requests with `stat`, `stale` packets go out before each answer.
+    fn fake_server(stat: Vec<u8>, stale: bool) -> SocketAddr {
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        socket.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        let addr = socket.local_addr().unwrap();
+        thread::spawn(move || {
+            let mut buf = [0; 64];
+            while let Ok((length, from)) = socket.recv_from(&mut buf) {
+                assert_eq!(buf[..2], MAGIC);
+                let (kind, session) = (buf[2], &buf[3..7]);
+                let body = match kind {
+                    TYPE_HANDSHAKE => format!("{}\0", TOKEN).into_bytes(),
+                    _ => {
+                        assert_eq!(buf[7..11], TOKEN.to_be_bytes());
+                        assert!(length == 11 || (length == 15 && buf[11..15] == [0; 4]));
+                        stat.clone()
+                    }
+                };
+                if stale {
+                    let mut other_session = vec![kind, session[0] ^ 1, session[1], session[2], session[3]];
+                    other_session.extend_from_slice(b"stale\0");
+                    socket.send_to(&other_session, from).unwrap();
+                    let other_kind = if kind == TYPE_HANDSHAKE { TYPE_STAT } else { TYPE_HANDSHAKE };
+                    let mut other_type = vec![other_kind];
+                    other_type.extend_from_slice(session);
+                    other_type.extend_from_slice(b"stale\0");
+                    socket.send_to(&other_type, from).unwrap();
+                }
+                let mut response = vec![kind];
+                response.extend_from_slice(session);
+                response.extend_from_slice(&body);
+                socket.send_to(&response, from).unwrap();
+            }
+        });
+        addr
+    }
+
+    fn basic_stat_response() -> Vec<u8> {
+        let mut stat = b"A Minecraft Server\0SMP\0world\x002\x0020\0".to_vec();
+        stat.extend_from_slice(&25566u16.to_le_bytes());
+        stat.extend_from_slice(b"127.0.0.1\0");
+        stat
+    }
+
+    fn full_stat_response() -> Vec<u8> {
+        let mut stat = b"splitnum\0\x80\0".to_vec();
+        for (key, value) in [
+            ("hostname", "Caf\u{e9}"),
+            ("gametype", "SMP"),
+            ("game_id", "MINECRAFT"),
+            ("version", "1.21.4"),
+            ("plugins", "Paper on 1.21.4-R0.1-SNAPSHOT: WorldEdit 7.3.0; LuckPerms 5.4.102"),
+            ("map", "world"),
+            ("numplayers", "2"),
+            ("maxplayers", "20"),
+            ("hostport", "25566"),
+            ("hostip", "127.0.0.1"),
+        ] {
+            // Query strings are ISO-8859-1.
+            stat.extend(key.chars().chain(['\0']).chain(value.chars()).chain(['\0']).map(|c| c as u8));
+        }
+        stat.push(0);
+        stat.extend_from_slice(b"\x01player_\0\0");
+        stat.extend_from_slice(b"Steve\0Alex\0\0");
+        stat
+    }
+
+    #[test]
+    fn parses_the_challenge_token() {
+        let mut client = QueryClient::connect(fake_server(Vec::new(), false)).unwrap();
+        assert_eq!(client.handshake().unwrap(), TOKEN);
+    }
+
+    #[test]
+    fn reads_the_basic_stat() {
+        let mut client = QueryClient::connect(fake_server(basic_stat_response(), false)).unwrap();
+        let stat = client.basic_stat().unwrap();
+        assert_eq!(
+            stat,
+            BasicStat {
+                motd: "A Minecraft Server".to_string(),
+                game_type: "SMP".to_string(),
+                map: "world".to_string(),
+                players_online: 2,
+                players_max: 20,
+                host_port: 25566,
+                host_ip: "127.0.0.1".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn reads_the_full_stat() {
+        let mut client = QueryClient::connect(fake_server(full_stat_response(), false)).unwrap();
+        let stat = client.full_stat().unwrap();
+        assert_eq!(stat.motd, "Caf\u{e9}");
+        assert_eq!(stat.game_id, "MINECRAFT");
+        assert_eq!(stat.version, "1.21.4");
+        assert_eq!(stat.server_mod.as_deref(), Some("Paper on 1.21.4-R0.1-SNAPSHOT"));
+        assert_eq!(stat.plugins, ["WorldEdit 7.3.0", "LuckPerms 5.4.102"]);
+        assert_eq!((stat.players_online, stat.players_max), (2, 20));
+        assert_eq!(stat.host_port, 25566);
+        assert_eq!(stat.players, ["Steve", "Alex"]);
+    }
+
+    #[test]
+    fn skips_packets_for_other_sessions() {
+        let mut client = QueryClient::connect(fake_server(basic_stat_response(), true)).unwrap();
+        assert_eq!(client.basic_stat().unwrap().motd, "A Minecraft Server");
+    }
+
+    #[test]
+    fn splits_the_plugin_string() {
+        assert_eq!(parse_plugins(""), (None, Vec::new()));
+        assert_eq!(parse_plugins("CraftBukkit on Bukkit 1.7.10"), (Some("CraftBukkit on Bukkit 1.7.10".to_string()), Vec::new()));
+        assert_eq!(
+            parse_plugins("Paper on 1.21.4: WorldEdit 7.3.0; LuckPerms 5.4"),
+            (
+                Some("Paper on 1.21.4".to_string()),
+                vec!["WorldEdit 7.3.0".to_string(), "LuckPerms 5.4".to_string()]
+            )
+        );
+    }
+}
@@ -0,0 +1,283 @@
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+use crate::{MinecraftVersion, ServerCommand, ServerProperties};
+
+pub const DEFAULT_RCON_PORT: u16 = 25575;
+
+const SERVERDATA_AUTH: i32 = 3;
+const SERVERDATA_AUTH_RESPONSE: i32 = 2;
+const SERVERDATA_EXECCOMMAND: i32 = 2;
+const SERVERDATA_RESPONSE_VALUE: i32 = 0;
+
+// The vanilla server drops command packets whose body is longer than this.
+const MAX_COMMAND_LENGTH: usize = 1446;
+// Responses are split every 4096 UTF-16 units before encoding, a fragment can take up to three bytes per unit.
+const MAX_PACKET_LENGTH: usize = 3 * 4096 + 10;
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, thiserror::Error)]
+pub enum RconError {
+    #[error("failed to read server.properties: {0}")]
+    Properties(std::io::Error),
+    #[error("RCON is disabled in server.properties")]
+    Disabled,
+    #[error("rcon.password is not set in server.properties")]
+    MissingPassword,
+    #[error("RCON authentication failed")]
+    AuthenticationFailed,
+    #[error("command is {0} bytes, RCON accepts at most {MAX_COMMAND_LENGTH}")]
+    CommandTooLong(usize),
+    #[error("invalid RCON packet: {0}")]
+    InvalidPacket(String),
+    #[error("RCON connection failed: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+struct Packet {
+    id: i32,
+    kind: i32,
+    body: Vec<u8>,
+}
+
+pub struct RconClient {
+    stream: TcpStream,
+    next_id: i32,
+    minecraft_version: Option<MinecraftVersion>,
+}
+
+impl RconClient {
+    pub fn connect<A: ToSocketAddrs>(addr: A, password: &str) -> Result<Self, RconError> {
+        let stream = TcpStream::connect(addr)?;
+        stream.set_read_timeout(Some(DEFAULT_TIMEOUT))?;
+        stream.set_write_timeout(Some(DEFAULT_TIMEOUT))?;
+        stream.set_nodelay(true)?;
+
+        let mut client = RconClient {
+            stream,
+            next_id: 1,
+            minecraft_version: None,
+        };
+        client.login(password)?;
+        Ok(client)
+    }
+
+    pub fn from_properties(properties: &ServerProperties) -> Result<Self, RconError> {
+        if properties.enable_rcon() != Some(true) {
+            return Err(RconError::Disabled);
+        }
+        let password = properties.rcon_password().ok_or(RconError::MissingPassword)?;
+        let host = properties.server_ip().unwrap_or("127.0.0.1");
+        let port = properties.rcon_port().unwrap_or(DEFAULT_RCON_PORT);
+        Self::connect((host, port), password)
+    }
+
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) -> Result<(), RconError> {
+        self.stream.set_read_timeout(timeout)?;
+        self.stream.set_write_timeout(timeout)?;
+        Ok(())
+    }
+
+    pub fn set_minecraft_version(&mut self, version: Option<MinecraftVersion>) {
+        self.minecraft_version = version;
+    }
+
+    pub fn send(&mut self, command: &ServerCommand) -> Result<String, RconError> {
+        self.command(&command.to_command(self.minecraft_version.as_ref()))
+    }
+
+    pub fn command(&mut self, command: &str) -> Result<String, RconError> {
+        if command.len() > MAX_COMMAND_LENGTH {
+            return Err(RconError::CommandTooLong(command.len()));
+        }
+
+        let id = self.next_id();
+        self.send_packet(id, SERVERDATA_EXECCOMMAND, command.as_bytes())?;
+        // Responses longer than 4096 bytes are split over several packets with no end marker,
+        // so follow up with a packet the server answers only after the command's response.
+        let sentinel = self.next_id();
+        self.send_packet(sentinel, SERVERDATA_RESPONSE_VALUE, b"")?;
+
+        let mut body = Vec::new();
+        loop {
+            let packet = self.receive_packet()?;
+            if packet.id == sentinel {
+                break;
+            }
+            if packet.id == id {
+                body.extend_from_slice(&packet.body);
+            }
+        }
+        Ok(String::from_utf8_lossy(&body).into_owned())
+    }
+
+    fn login(&mut self, password: &str) -> Result<(), RconError> {
+        let id = self.next_id();
+        self.send_packet(id, SERVERDATA_AUTH, password.as_bytes())?;
+        loop {
+            let packet = self.receive_packet()?;
+            if packet.kind != SERVERDATA_AUTH_RESPONSE {
+                continue;
+            }
+            return if packet.id == id {
+                Ok(())
+            } else {
+                Err(RconError::AuthenticationFailed)
+            };
+        }
+    }
+
+    fn next_id(&mut self) -> i32 {
+        let id = self.next_id;
+        self.next_id = self.next_id.checked_add(1).unwrap_or(1);
+        id
+    }
+
+    fn send_packet(&mut self, id: i32, kind: i32, body: &[u8]) -> Result<(), RconError> {
+        let mut packet = Vec::with_capacity(body.len() + 14);
+        packet.extend_from_slice(&(body.len() as i32 + 10).to_le_bytes());
+        packet.extend_from_slice(&id.to_le_bytes());
+        packet.extend_from_slice(&kind.to_le_bytes());
+        packet.extend_from_slice(body);
+        packet.extend_from_slice(&[0, 0]);
+        self.stream.write_all(&packet)?;
+        Ok(())
+    }
+
+    fn receive_packet(&mut self) -> Result<Packet, RconError> {
+        let mut length = [0; 4];
+        self.stream.read_exact(&mut length)?;
+        let length = i32::from_le_bytes(length);
+        if !(10..=MAX_PACKET_LENGTH as i32).contains(&length) {
+            return Err(RconError::InvalidPacket(format!("length {}", length)));
+        }
+
+        let mut payload = vec![0; length as usize];
+        self.stream.read_exact(&mut payload)?;
+        let id = i32::from_le_bytes(payload[0..4].try_into().unwrap());
+        let kind = i32::from_le_bytes(payload[4..8].try_into().unwrap());
+        let body = &payload[8..];
+        let end = body.iter().position(|&b| b == 0).unwrap_or(body.len());
+        Ok(Packet {
+            id,
+            kind,
+            body: body[..end].to_vec(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{SocketAddr, TcpListener};
+    use std::thread;
+
+    use super::*;
+
+    fn fake_server<F: FnOnce(TcpStream) + Send + 'static>(handler: F) -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            handler(stream);
+        });
+        addr
+    }
+
+    fn read_packet(stream: &mut TcpStream) -> (i32, i32, Vec<u8>) {
+        let mut length = [0; 4];
+        stream.read_exact(&mut length).unwrap();
+        let mut payload = vec![0; i32::from_le_bytes(length) as usize];
+        stream.read_exact(&mut payload).unwrap();
+        let id = i32::from_le_bytes(payload[0..4].try_into().unwrap());
+        let kind = i32::from_le_bytes(payload[4..8].try_into().unwrap());
+        (id, kind, payload[8..payload.len() - 2].to_vec())
+    }
+
+    fn write_packet(stream: &mut TcpStream, id: i32, kind: i32, body: &[u8]) {
+        let mut packet = Vec::new();
+        packet.extend_from_slice(&(body.len() as i32 + 10).to_le_bytes());
+        packet.extend_from_slice(&id.to_le_bytes());
+        packet.extend_from_slice(&kind.to_le_bytes());
+        packet.extend_from_slice(body);
+        packet.extend_from_slice(&[0, 0]);
+        stream.write_all(&packet).unwrap();
+    }
+
+    // Like vanilla, an empty response packet precedes the auth response, which carries -1 on failure.
+    fn accept_login(stream: &mut TcpStream, password: &str) -> bool {
+        let (id, kind, body) = read_packet(stream);
+        assert_eq!(kind, SERVERDATA_AUTH);
+        let accepted = body == password.as_bytes();
+        write_packet(stream, 0, SERVERDATA_RESPONSE_VALUE, b"");
+        write_packet(stream, if accepted { id } else { -1 }, SERVERDATA_AUTH_RESPONSE, b"");
+        accepted
+    }
+
+    // Answers one command with `response` split into `chunk` sized packets, then the sentinel.
+    fn respond(stream: &mut TcpStream, response: &[u8], chunk: usize) -> String {
+        let (id, kind, command) = read_packet(stream);
+        assert_eq!(kind, SERVERDATA_EXECCOMMAND);
+        let (sentinel, kind, _) = read_packet(stream);
+        assert_eq!(kind, SERVERDATA_RESPONSE_VALUE);
+        for part in response.chunks(chunk) {
+            write_packet(stream, id, SERVERDATA_RESPONSE_VALUE, part);
+        }
+        write_packet(stream, sentinel, SERVERDATA_RESPONSE_VALUE, b"");
+        String::from_utf8(command).unwrap()
+    }
+
+    #[test]
+    fn logs_in_and_runs_a_command() {
+        let addr = fake_server(|mut stream| {
+            assert!(accept_login(&mut stream, "secret"));
+            assert_eq!(respond(&mut stream, b"There are 0 of a max of 20 players online", 4096), "list");
+        });
+        let mut client = RconClient::connect(addr, "secret").unwrap();
+        assert_eq!(client.command("list").unwrap(), "There are 0 of a max of 20 players online");
+    }
+
+    #[test]
+    fn rejects_a_wrong_password() {
+        let addr = fake_server(|mut stream| {
+            assert!(!accept_login(&mut stream, "secret"));
+        });
+        let result = RconClient::connect(addr, "wrong");
+        assert!(matches!(result, Err(RconError::AuthenticationFailed)));
+    }
+
+    #[test]
+    fn reassembles_fragmented_responses() {
+        let response: Vec<u8> = (0..10_000).map(|i| b'a' + (i % 26) as u8).collect();
+        let expected = String::from_utf8(response.clone()).unwrap();
+        let addr = fake_server(move |mut stream| {
+            accept_login(&mut stream, "secret");
+            respond(&mut stream, &response, 4096);
+        });
+        let mut client = RconClient::connect(addr, "secret").unwrap();
+        assert_eq!(client.command("banlist").unwrap(), expected);
+    }
+
+    #[test]
+    fn accepts_fragments_longer_than_4096_bytes() {
+        let expected = "\u{2714}".repeat(5000);
+        let response = expected.clone().into_bytes();
+        let addr = fake_server(move |mut stream| {
+            accept_login(&mut stream, "secret");
+            respond(&mut stream, &response, 3 * 4096);
+        });
+        let mut client = RconClient::connect(addr, "secret").unwrap();
+        assert_eq!(client.command("list").unwrap(), expected);
+    }
+
+    #[test]
+    fn rejects_commands_over_the_length_limit() {
+        let addr = fake_server(|mut stream| {
+            accept_login(&mut stream, "secret");
+        });
+        let mut client = RconClient::connect(addr, "secret").unwrap();
+        let command = "a".repeat(MAX_COMMAND_LENGTH + 1);
+        let result = client.command(&command);
+        assert!(matches!(result, Err(RconError::CommandTooLong(length)) if length == MAX_COMMAND_LENGTH + 1));
+    }
+}
@@ -0,0 +1,223 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::{ExitReport, MinecraftServer, ServerHandle, StopPolicy};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestartPolicy {
+    Never,
+    OnFailure,
+    Always,
+}
+
+#[derive(Debug, Clone)]
+pub struct RestartEvent {
+    pub attempt: u32,
+    pub report: ExitReport,
+    pub crashed: bool,
+    pub delay: Duration,
+}
+
+#[derive(Debug, Clone)]
+pub struct SupervisorExit {
+    pub report: ExitReport,
+    pub restarts: u32,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SupervisorError {
+    #[error("server crashed after {restarts} restarts within {window:?}, last exit: {}", .report.status)]
+    CrashLoop {
+        restarts: usize,
+        window: Duration,
+        report: ExitReport,
+    },
+    #[error("failed to supervise server: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SupervisorControl {
+    stop: Arc<AtomicBool>,
+}
+
+impl SupervisorControl {
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::SeqCst);
+    }
+
+    fn stop_requested(&self) -> bool {
+        self.stop.load(Ordering::SeqCst)
+    }
+}
+
+type StartHook = Box<dyn FnMut(&mut ServerHandle) + Send>;
+type RestartHook = Box<dyn FnMut(&RestartEvent) + Send>;
+
+pub struct Supervisor {
+    server: MinecraftServer,
+    policy: RestartPolicy,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    max_restarts: usize,
+    restart_window: Duration,
+    stop_policy: StopPolicy,
+    control: SupervisorControl,
+    on_start: Vec<StartHook>,
+    before_restart: Vec<RestartHook>,
+}
+
+impl Supervisor {
+    pub fn new(server: MinecraftServer) -> Self {
+        Supervisor {
+            server,
+            policy: RestartPolicy::OnFailure,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+            max_restarts: 5,
+            restart_window: Duration::from_secs(300),
+            stop_policy: StopPolicy::default(),
+            control: SupervisorControl::default(),
+            on_start: Vec::new(),
+            before_restart: Vec::new(),
+        }
+    }
+
+    pub fn policy(mut self, policy: RestartPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    pub fn backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max.max(initial);
+        self
+    }
+
+    pub fn max_restarts(mut self, restarts: usize, window: Duration) -> Self {
+        self.max_restarts = restarts;
+        self.restart_window = window;
+        self
+    }
+
+    pub fn stop_policy(mut self, policy: StopPolicy) -> Self {
+        self.stop_policy = policy;
+        self
+    }
+
+    pub fn on_start<F: FnMut(&mut ServerHandle) + Send + 'static>(mut self, hook: F) -> Self {
+        self.on_start.push(Box::new(hook));
+        self
+    }
+
+    pub fn before_restart<F: FnMut(&RestartEvent) + Send + 'static>(mut self, hook: F) -> Self {
+        self.before_restart.push(Box::new(hook));
+        self
+    }
+
+    pub fn control(&self) -> SupervisorControl {
+        self.control.clone()
+    }
+
+    pub fn run(&mut self) -> Result<SupervisorExit, SupervisorError> {
+        let mut restarts = 0;
+        let mut consecutive_crashes = 0;
+        let mut recent_restarts: VecDeque<Instant> = VecDeque::new();
+
+        loop {
+            let mut handle = self.server.spawn()?;
+            for hook in &mut self.on_start {
+                hook(&mut handle);
+            }
+            let report = self.supervise(&mut handle)?;
+
+            let crashed = report.is_abnormal();
+            let restart = !self.control.stop_requested()
+                && match self.policy {
+                    RestartPolicy::Never => false,
+                    RestartPolicy::OnFailure => crashed,
+                    RestartPolicy::Always => true,
+                };
+            if !restart {
+                return Ok(SupervisorExit { report, restarts });
+            }
+
+            let now = Instant::now();
+            while recent_restarts
+                .front()
+                .is_some_and(|at| now.duration_since(*at) > self.restart_window)
+            {
+                recent_restarts.pop_front();
+            }
+            if crashed && recent_restarts.len() >= self.max_restarts {
+                return Err(SupervisorError::CrashLoop {
+                    restarts: recent_restarts.len(),
+                    window: self.restart_window,
+                    report,
+                });
+            }
+
+            // A crash after a healthy run starts the backoff over, only quick successive crashes escalate it.
+            consecutive_crashes = match crashed {
+                false => 0,
+                true if report.runtime > self.restart_window => 1,
+                true => consecutive_crashes + 1,
+            };
+            let delay = self.backoff_delay(consecutive_crashes);
+            restarts += 1;
+            let event = RestartEvent {
+                attempt: restarts,
+                report,
+                crashed,
+                delay,
+            };
+            for hook in &mut self.before_restart {
+                hook(&event);
+            }
+
+            if !self.sleep(delay) {
+                return Ok(SupervisorExit {
+                    report: event.report,
+                    restarts: restarts - 1,
+                });
+            }
+            if crashed {
+                recent_restarts.push_back(Instant::now());
+            }
+        }
+    }
+
+    fn supervise(&self, handle: &mut ServerHandle) -> Result<ExitReport, std::io::Error> {
+        loop {
+            if handle.try_wait()?.is_some() {
+                return handle.wait_with_report();
+            }
+            if self.control.stop_requested() {
+                handle.stop_with(self.stop_policy)?;
+                return handle.wait_with_report();
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+    }
+
+    fn backoff_delay(&self, consecutive_crashes: u32) -> Duration {
+        let factor = 2u32.saturating_pow(consecutive_crashes.saturating_sub(1));
+        self.initial_backoff.saturating_mul(factor).min(self.max_backoff)
+    }
+
+    fn sleep(&self, delay: Duration) -> bool {
+        let deadline = Instant::now() + delay;
+        loop {
+            if self.control.stop_requested() {
+                return false;
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return true;
+            }
+            std::thread::sleep((deadline - now).min(Duration::from_millis(100)));
+        }
+    }
+}
@@ -0,0 +1,713 @@
+use std::fmt::{self, Display};
+
+use serde_json::{Map, Value};
+
+const SECTION: char = '\u{a7}';
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TextColor {
+    Black,
+    DarkBlue,
+    DarkGreen,
+    DarkAqua,
+    DarkRed,
+    DarkPurple,
+    Gold,
+    Gray,
+    DarkGray,
+    Blue,
+    Green,
+    Aqua,
+    Red,
+    LightPurple,
+    Yellow,
+    White,
+    Rgb(u8, u8, u8),
+}
+
+// name, legacy code, rgb, ANSI foreground
+const NAMED_COLORS: [(TextColor, &str, char, u32, u8); 16] = [
+    (TextColor::Black, "black", '0', 0x000000, 30),
+    (TextColor::DarkBlue, "dark_blue", '1', 0x0000AA, 34),
+    (TextColor::DarkGreen, "dark_green", '2', 0x00AA00, 32),
+    (TextColor::DarkAqua, "dark_aqua", '3', 0x00AAAA, 36),
+    (TextColor::DarkRed, "dark_red", '4', 0xAA0000, 31),
+    (TextColor::DarkPurple, "dark_purple", '5', 0xAA00AA, 35),
+    (TextColor::Gold, "gold", '6', 0xFFAA00, 33),
+    (TextColor::Gray, "gray", '7', 0xAAAAAA, 37),
+    (TextColor::DarkGray, "dark_gray", '8', 0x555555, 90),
+    (TextColor::Blue, "blue", '9', 0x5555FF, 94),
+    (TextColor::Green, "green", 'a', 0x55FF55, 92),
+    (TextColor::Aqua, "aqua", 'b', 0x55FFFF, 96),
+    (TextColor::Red, "red", 'c', 0xFF5555, 91),
+    (TextColor::LightPurple, "light_purple", 'd', 0xFF55FF, 95),
+    (TextColor::Yellow, "yellow", 'e', 0xFFFF55, 93),
+    (TextColor::White, "white", 'f', 0xFFFFFF, 97),
+];
+
+impl TextColor {
+    pub fn parse(name: &str) -> Option<TextColor> {
+        if let Some(hex) = name.strip_prefix('#') {
+            let rgb = u32::from_str_radix(hex, 16).ok().filter(|_| hex.len() == 6)?;
+            return Some(TextColor::Rgb((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8));
+        }
+        NAMED_COLORS.iter().find(|named| named.1 == name).map(|named| named.0)
+    }
+
+    pub fn from_legacy_code(code: char) -> Option<TextColor> {
+        let code = code.to_ascii_lowercase();
+        NAMED_COLORS.iter().find(|named| named.2 == code).map(|named| named.0)
+    }
+
+    pub fn name(&self) -> String {
+        match self.named() {
+            Some(named) => named.1.to_string(),
+            None => format!("#{:06X}", self.rgb()),
+        }
+    }
+
+    pub fn legacy_code(&self) -> Option<char> {
+        self.named().map(|named| named.2)
+    }
+
+    pub fn rgb(&self) -> u32 {
+        match (self, self.named()) {
+            (TextColor::Rgb(r, g, b), _) => (*r as u32) << 16 | (*g as u32) << 8 | *b as u32,
+            (_, Some(named)) => named.3,
+            _ => unreachable!(),
+        }
+    }
+
+    fn named(&self) -> Option<&'static (TextColor, &'static str, char, u32, u8)> {
+        NAMED_COLORS.iter().find(|named| named.0 == *self)
+    }
+
+    fn ansi(&self) -> String {
+        match (self, self.named()) {
+            (TextColor::Rgb(r, g, b), _) => format!("38;2;{};{};{}", r, g, b),
+            (_, Some(named)) => named.4.to_string(),
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl Display for TextColor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name())
+    }
+}
+
+// Unset fields inherit from the parent component.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TextStyle {
+    pub color: Option<TextColor>,
+    pub bold: Option<bool>,
+    pub italic: Option<bool>,
+    pub underlined: Option<bool>,
+    pub strikethrough: Option<bool>,
+    pub obfuscated: Option<bool>,
+}
+
+impl TextStyle {
+    fn inherit(&self, parent: &TextStyle) -> TextStyle {
+        TextStyle {
+            color: self.color.or(parent.color),
+            bold: self.bold.or(parent.bold),
+            italic: self.italic.or(parent.italic),
+            underlined: self.underlined.or(parent.underlined),
+            strikethrough: self.strikethrough.or(parent.strikethrough),
+            obfuscated: self.obfuscated.or(parent.obfuscated),
+        }
+    }
+
+    fn flags(&self) -> [(Option<bool>, &'static str, char); 5] {
+        [
+            (self.obfuscated, "obfuscated", 'k'),
+            (self.bold, "bold", 'l'),
+            (self.strikethrough, "strikethrough", 'm'),
+            (self.underlined, "underlined", 'n'),
+            (self.italic, "italic", 'o'),
+        ]
+    }
+
+    fn is_plain(&self) -> bool {
+        self.color.is_none() && self.flags().iter().all(|(flag, _, _)| *flag != Some(true))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TextContent {
+    Text(String),
+    Translate {
+        key: String,
+        fallback: Option<String>,
+        with: Vec<TextComponent>,
+    },
+    Keybind(String),
+    Selector(String),
+    Score { name: String, objective: String },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextComponent {
+    pub content: TextContent,
+    pub style: TextStyle,
+    pub extra: Vec<TextComponent>,
+    // Click and hover events, fonts and the like are kept as is so they survive a round trip.
+    other: Map<String, Value>,
+}
+
+impl TextComponent {
+    pub fn text<T: Into<String>>(text: T) -> Self {
+        TextComponent::new(TextContent::Text(text.into()))
+    }
+
+    pub fn translate<T: Into<String>>(key: T, with: Vec<TextComponent>) -> Self {
+        TextComponent::new(TextContent::Translate {
+            key: key.into(),
+            fallback: None,
+            with,
+        })
+    }
+
+    pub fn color(mut self, color: TextColor) -> Self {
+        self.style.color = Some(color);
+        self
+    }
+
+    pub fn bold(mut self, bold: bool) -> Self {
+        self.style.bold = Some(bold);
+        self
+    }
+
+    pub fn italic(mut self, italic: bool) -> Self {
+        self.style.italic = Some(italic);
+        self
+    }
+
+    pub fn underlined(mut self, underlined: bool) -> Self {
+        self.style.underlined = Some(underlined);
+        self
+    }
+
+    pub fn strikethrough(mut self, strikethrough: bool) -> Self {
+        self.style.strikethrough = Some(strikethrough);
+        self
+    }
+
+    pub fn obfuscated(mut self, obfuscated: bool) -> Self {
+        self.style.obfuscated = Some(obfuscated);
+        self
+    }
+
+    pub fn append(mut self, component: TextComponent) -> Self {
+        self.extra.push(component);
+        self
+    }
+
+    pub fn parse(json: &str) -> Result<TextComponent, serde_json::Error> {
+        Ok(TextComponent::from_json(&serde_json::from_str(json)?))
+    }
+
+    pub fn from_json(json: &Value) -> TextComponent {
+        match json {
+            // The client renders section sign codes inside plain strings too.
+            Value::String(text) => TextComponent::from_legacy(text),
+            Value::Array(components) => {
+                let mut components = components.iter().map(TextComponent::from_json);
+                let mut first = components.next().unwrap_or_else(|| TextComponent::text(""));
+                first.extra.extend(components);
+                first
+            }
+            Value::Object(object) => TextComponent::from_object(object),
+            Value::Null => TextComponent::text(""),
+            other => TextComponent::text(other.to_string()),
+        }
+    }
+
+    pub fn from_legacy(text: &str) -> TextComponent {
+        TextComponent::from_legacy_with(text, SECTION)
+    }
+
+    pub fn from_legacy_with(text: &str, marker: char) -> TextComponent {
+        let mut parts = Vec::new();
+        let mut style = TextStyle::default();
+        let mut current = String::new();
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            let Some(code) = chars.peek().filter(|_| c == marker).map(|code| code.to_ascii_lowercase()) else {
+                current.push(c);
+                continue;
+            };
+            chars.next();
+            if !current.is_empty() {
+                parts.push(TextComponent::styled(std::mem::take(&mut current), style.clone()));
+            }
+            match code {
+                'k' => style.obfuscated = Some(true),
+                'l' => style.bold = Some(true),
+                'm' => style.strikethrough = Some(true),
+                'n' => style.underlined = Some(true),
+                'o' => style.italic = Some(true),
+                'r' => style = TextStyle::default(),
+                // Bukkit spells hex colors as §x§R§R§G§G§B§B.
+                'x' => {
+                    let digits: String = (0..6)
+                        .map_while(|_| {
+                            chars.next_if_eq(&marker)?;
+                            chars.next_if(char::is_ascii_hexdigit)
+                        })
+                        .collect();
+                    if let Some(color) = TextColor::parse(&format!("#{}", digits)) {
+                        style = TextStyle {
+                            color: Some(color),
+                            ..TextStyle::default()
+                        };
+                    }
+                }
+                code => {
+                    if let Some(color) = TextColor::from_legacy_code(code) {
+                        style = TextStyle {
+                            color: Some(color),
+                            ..TextStyle::default()
+                        };
+                    }
+                }
+            }
+        }
+        if !current.is_empty() || parts.is_empty() {
+            parts.push(TextComponent::styled(current, style));
+        }
+
+        if parts.len() == 1 {
+            return parts.remove(0);
+        }
+        let mut root = TextComponent::text("");
+        root.extra = parts;
+        root
+    }
+
+    pub fn to_json(&self) -> Value {
+        if let TextContent::Text(text) = &self.content
+            && self.style == TextStyle::default()
+            && self.extra.is_empty()
+            && self.other.is_empty()
+        {
+            return Value::String(text.clone());
+        }
+
+        let mut object = Map::new();
+        match &self.content {
+            TextContent::Text(text) => {
+                object.insert("text".to_string(), Value::String(text.clone()));
+            }
+            TextContent::Translate { key, fallback, with } => {
+                object.insert("translate".to_string(), Value::String(key.clone()));
+                if let Some(fallback) = fallback {
+                    object.insert("fallback".to_string(), Value::String(fallback.clone()));
+                }
+                if !with.is_empty() {
+                    object.insert("with".to_string(), with.iter().map(TextComponent::to_json).collect());
+                }
+            }
+            TextContent::Keybind(keybind) => {
+                object.insert("keybind".to_string(), Value::String(keybind.clone()));
+            }
+            TextContent::Selector(selector) => {
+                object.insert("selector".to_string(), Value::String(selector.clone()));
+            }
+            TextContent::Score { name, objective } => {
+                let mut score = Map::new();
+                score.insert("name".to_string(), Value::String(name.clone()));
+                score.insert("objective".to_string(), Value::String(objective.clone()));
+                object.insert("score".to_string(), Value::Object(score));
+            }
+        }
+        if let Some(color) = self.style.color {
+            object.insert("color".to_string(), Value::String(color.name()));
+        }
+        for (flag, name, _) in self.style.flags() {
+            if let Some(flag) = flag {
+                object.insert(name.to_string(), Value::Bool(flag));
+            }
+        }
+        object.extend(self.other.clone());
+        if !self.extra.is_empty() {
+            object.insert("extra".to_string(), self.extra.iter().map(TextComponent::to_json).collect());
+        }
+        Value::Object(object)
+    }
+
+    pub fn to_legacy(&self) -> String {
+        let mut legacy = String::new();
+        let mut previous = TextStyle::default();
+        for (text, style) in self.segments() {
+            if style != previous {
+                match style.color {
+                    Some(color) => match color.legacy_code() {
+                        Some(code) => legacy.extend([SECTION, code]),
+                        None => {
+                            legacy.extend([SECTION, 'x']);
+                            for digit in format!("{:06x}", color.rgb()).chars() {
+                                legacy.extend([SECTION, digit]);
+                            }
+                        }
+                    },
+                    None => legacy.extend([SECTION, 'r']),
+                }
+                for (flag, _, code) in style.flags() {
+                    if flag == Some(true) {
+                        legacy.extend([SECTION, code]);
+                    }
+                }
+                previous = style;
+            }
+            legacy.push_str(&text);
+        }
+        legacy
+    }
+
+    pub fn to_plain(&self) -> String {
+        self.segments().into_iter().map(|(text, _)| text).collect()
+    }
+
+    pub fn to_ansi(&self) -> String {
+        let mut ansi = String::new();
+        let mut previous = TextStyle::default();
+        for (text, style) in self.segments() {
+            if style != previous {
+                if !previous.is_plain() {
+                    ansi.push_str("\x1b[0m");
+                }
+                let mut codes = Vec::new();
+                if let Some(color) = style.color {
+                    codes.push(color.ansi());
+                }
+                for (flag, code) in [
+                    (style.bold, "1"),
+                    (style.italic, "3"),
+                    (style.underlined, "4"),
+                    (style.strikethrough, "9"),
+                ] {
+                    if flag == Some(true) {
+                        codes.push(code.to_string());
+                    }
+                }
+                if !codes.is_empty() {
+                    ansi.push_str(&format!("\x1b[{}m", codes.join(";")));
+                }
+                previous = style;
+            }
+            ansi.push_str(&text);
+        }
+        if !previous.is_plain() {
+            ansi.push_str("\x1b[0m");
+        }
+        ansi
+    }
+
+    pub fn to_html(&self) -> String {
+        let mut html = String::new();
+        for (text, style) in self.segments() {
+            let mut css = Vec::new();
+            if let Some(color) = style.color {
+                css.push(format!("color: #{:06x}", color.rgb()));
+            }
+            if style.bold == Some(true) {
+                css.push("font-weight: bold".to_string());
+            }
+            if style.italic == Some(true) {
+                css.push("font-style: italic".to_string());
+            }
+            let decorations: Vec<&str> = [(style.underlined, "underline"), (style.strikethrough, "line-through")]
+                .into_iter()
+                .filter(|(flag, _)| *flag == Some(true))
+                .map(|(_, decoration)| decoration)
+                .collect();
+            if !decorations.is_empty() {
+                css.push(format!("text-decoration: {}", decorations.join(" ")));
+            }
+
+            let escaped = escape_html(&text);
+            if css.is_empty() {
+                html.push_str(&escaped);
+            } else {
+                html.push_str(&format!("<span style=\"{}\">{}</span>", css.join("; "), escaped));
+            }
+        }
+        html
+    }
+
+    fn new(content: TextContent) -> Self {
+        TextComponent {
+            content,
+            style: TextStyle::default(),
+            extra: Vec::new(),
+            other: Map::new(),
+        }
+    }
+
+    fn styled(text: String, style: TextStyle) -> Self {
+        let mut component = TextComponent::text(text);
+        component.style = style;
+        component
+    }
+
+    fn from_object(object: &Map<String, Value>) -> TextComponent {
+        let string = |key: &str| object.get(key).and_then(Value::as_str).map(str::to_string);
+        let mut other = object.clone();
+        let mut take = |key: &str| other.remove(key);
+
+        let mut component = if let Some(key) = string("translate") {
+            take("translate");
+            take("fallback");
+            let with = take("with")
+                .and_then(|with| with.as_array().cloned())
+                .unwrap_or_default()
+                .iter()
+                .map(TextComponent::from_json)
+                .collect();
+            TextComponent::new(TextContent::Translate {
+                key,
+                fallback: string("fallback"),
+                with,
+            })
+        } else if let Some(keybind) = string("keybind") {
+            take("keybind");
+            TextComponent::new(TextContent::Keybind(keybind))
+        } else if let Some(selector) = string("selector") {
+            take("selector");
+            TextComponent::new(TextContent::Selector(selector))
+        } else if let Some(score) = object.get("score").and_then(Value::as_object) {
+            take("score");
+            let field = |key: &str| score.get(key).and_then(Value::as_str).unwrap_or_default().to_string();
+            TextComponent::new(TextContent::Score {
+                name: field("name"),
+                objective: field("objective"),
+            })
+        } else {
+            let text = take("text").map_or_else(String::new, |text| match text {
+                Value::String(text) => text,
+                other => other.to_string(),
+            });
+            let parsed = TextComponent::from_legacy(&text);
+            if parsed.extra.is_empty() && parsed.style == TextStyle::default() {
+                TextComponent::text(text)
+            } else {
+                // Keep the legacy styled parts as a child so the object's own style still applies to them.
+                let mut component = TextComponent::text("");
+                component.extra.push(parsed);
+                component
+            }
+        };
+
+        component.style = TextStyle {
+            color: take("color").and_then(|color| TextColor::parse(color.as_str()?)),
+            bold: take("bold").and_then(|flag| flag.as_bool()),
+            italic: take("italic").and_then(|flag| flag.as_bool()),
+            underlined: take("underlined").and_then(|flag| flag.as_bool()),
+            strikethrough: take("strikethrough").and_then(|flag| flag.as_bool()),
+            obfuscated: take("obfuscated").and_then(|flag| flag.as_bool()),
+        };
+        if let Some(extra) = take("extra").and_then(|extra| extra.as_array().cloned()) {
+            component.extra.extend(extra.iter().map(TextComponent::from_json));
+        }
+        component.other = other;
+        component
+    }
+
+    fn segments(&self) -> Vec<(String, TextStyle)> {
+        let mut segments = Vec::new();
+        self.collect_segments(&TextStyle::default(), &mut segments);
+        segments
+    }
+
+    fn collect_segments(&self, parent: &TextStyle, segments: &mut Vec<(String, TextStyle)>) {
+        let style = self.style.inherit(parent);
+        fn push(segments: &mut Vec<(String, TextStyle)>, text: &str, style: &TextStyle) {
+            if !text.is_empty() {
+                segments.push((text.to_string(), style.clone()));
+            }
+        }
+        match &self.content {
+            TextContent::Text(text) => push(segments, text, &style),
+            TextContent::Keybind(keybind) => push(segments, keybind, &style),
+            TextContent::Selector(selector) => push(segments, selector, &style),
+            TextContent::Score { .. } => {}
+            TextContent::Translate { key, fallback, with } => {
+                // Without the language files the fallback or the key itself is the best format available.
+                let format = fallback.as_deref().unwrap_or(key);
+                let mut next_arg = 0;
+                let mut rest = format;
+                while let Some(start) = rest.find('%') {
+                    push(segments, &rest[..start], &style);
+                    let spec = &rest[start + 1..];
+                    let (index, len) = if spec.starts_with('s') {
+                        next_arg += 1;
+                        (Some(next_arg - 1), 1)
+                    } else if let Some(end) = spec.find("$s")
+                        && let Ok(position) = spec[..end].parse::<usize>()
+                    {
+                        (Some(position.saturating_sub(1)), end + 2)
+                    } else if spec.starts_with('%') {
+                        push(segments, "%", &style);
+                        (None, 1)
+                    } else {
+                        push(segments, "%", &style);
+                        (None, 0)
+                    };
+                    if let Some(arg) = index.and_then(|index| with.get(index)) {
+                        arg.collect_segments(&style, segments);
+                    }
+                    rest = &spec[len..];
+                }
+                push(segments, rest, &style);
+            }
+        }
+        for child in &self.extra {
+            child.collect_segments(&style, segments);
+        }
+    }
+}
+
+impl Display for TextComponent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_plain())
+    }
+}
+
+impl From<&str> for TextComponent {
+    fn from(text: &str) -> Self {
+        TextComponent::text(text)
+    }
+}
+
+impl From<String> for TextComponent {
+    fn from(text: String) -> Self {
+        TextComponent::text(text)
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            '\n' => escaped.push_str("<br>"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn parses_legacy_codes() {
+        let text = TextComponent::from_legacy("\u{a7}aGreen \u{a7}lbold\u{a7}r plain \u{a7}x\u{a7}f\u{a7}f\u{a7}0\u{a7}0\u{a7}0\u{a7}0red");
+        assert_eq!(text.to_plain(), "Green bold plain red");
+        assert_eq!(
+            text.extra,
+            [
+                TextComponent::text("Green ").color(TextColor::Green),
+                TextComponent::text("bold").color(TextColor::Green).bold(true),
+                TextComponent::text(" plain "),
+                TextComponent::text("red").color(TextColor::Rgb(0xff, 0, 0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn a_color_code_resets_formatting() {
+        let text = TextComponent::from_legacy("\u{a7}l\u{a7}nbold\u{a7}cred");
+        assert_eq!(text.extra[1], TextComponent::text("red").color(TextColor::Red));
+        assert_eq!(TextComponent::from_legacy_with("&6gold", '&'), TextComponent::text("gold").color(TextColor::Gold));
+    }
+
+    #[test]
+    fn parses_json_components() {
+        let text = TextComponent::from_json(&json!({
+            "text": "Hello ",
+            "color": "gold",
+            "bold": true,
+            "extra": [{"text": "world", "bold": false}, "!"],
+        }));
+        assert_eq!(text.to_plain(), "Hello world!");
+        assert_eq!(text.style.color, Some(TextColor::Gold));
+        assert_eq!(text.extra[0].style.bold, Some(false));
+
+        let array = TextComponent::from_json(&json!(["a", {"text": "b", "color": "#12AB34"}]));
+        assert_eq!(array.to_plain(), "ab");
+        assert_eq!(array.extra[0].style.color, Some(TextColor::Rgb(0x12, 0xab, 0x34)));
+    }
+
+    #[test]
+    fn substitutes_translation_arguments() {
+        let text = TextComponent::from_json(&json!({
+            "translate": "chat.type.text",
+            "fallback": "<%s> %s (%2$s, 100%%)",
+            "with": ["Steve", {"text": "hi", "color": "red"}],
+        }));
+        assert_eq!(text.to_plain(), "<Steve> hi (hi, 100%)");
+        assert_eq!(TextComponent::translate("%s joined", vec!["Alex".into()]).to_plain(), "Alex joined");
+    }
+
+    #[test]
+    fn round_trips_json() {
+        let json = json!({
+            "text": "",
+            "extra": [
+                {"text": "Click", "color": "aqua", "underlined": true, "clickEvent": {"action": "open_url", "value": "https://example.com"}},
+                {"keybind": "key.jump"},
+                {"selector": "@p"},
+                {"score": {"name": "Steve", "objective": "kills"}},
+            ],
+        });
+        let text = TextComponent::from_json(&json);
+        assert_eq!(text.to_json(), json);
+        assert_eq!(TextComponent::parse(&text.to_json().to_string()).unwrap(), text);
+        assert_eq!(TextComponent::text("plain").to_json(), json!("plain"));
+    }
+
+    #[test]
+    fn converts_between_json_and_legacy() {
+        let text = TextComponent::text("")
+            .append(TextComponent::text("Hi ").color(TextColor::Gold).bold(true))
+            .append(TextComponent::text("there").color(TextColor::Rgb(0x12, 0xab, 0x34)))
+            .append(TextComponent::text(" you"));
+        let legacy = text.to_legacy();
+        assert_eq!(
+            legacy,
+            "\u{a7}6\u{a7}lHi \u{a7}x\u{a7}1\u{a7}2\u{a7}a\u{a7}b\u{a7}3\u{a7}4there\u{a7}r you"
+        );
+        assert_eq!(TextComponent::from_legacy(&legacy).to_legacy(), legacy);
+    }
+
+    #[test]
+    fn renders_ansi() {
+        let text = TextComponent::text("")
+            .append(TextComponent::text("warn").color(TextColor::Yellow).bold(true))
+            .append(TextComponent::text(" ok"));
+        assert_eq!(text.to_ansi(), "\u{1b}[93;1mwarn\u{1b}[0m ok");
+        assert_eq!(TextComponent::text("x").color(TextColor::Rgb(1, 2, 3)).to_ansi(), "\u{1b}[38;2;1;2;3mx\u{1b}[0m");
+    }
+
+    #[test]
+    fn renders_escaped_html() {
+        let text = TextComponent::text("")
+            .append(TextComponent::text("<b>&</b>").color(TextColor::Red).italic(true))
+            .append(TextComponent::text("\nline"));
+        assert_eq!(
+            text.to_html(),
+            "<span style=\"color: #ff5555; font-style: italic\">&lt;b&gt;&amp;&lt;/b&gt;</span><br>line"
+        );
+    }
+}
@@ -0,0 +1,57 @@
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MinecraftVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl MinecraftVersion {
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        MinecraftVersion { major, minor, patch }
+    }
+
+    pub fn required_java(&self) -> u32 {
+        match *self {
+            v if v < MinecraftVersion::new(1, 17, 0) => 8,
+            v if v < MinecraftVersion::new(1, 18, 0) => 16,
+            v if v < MinecraftVersion::new(1, 20, 5) => 17,
+            v if v < MinecraftVersion::new(26, 1, 0) => 21,
+            _ => 25,
+        }
+    }
+}
+
+impl FromStr for MinecraftVersion {
+    type Err = String;
+
+    // Accepts release ids such as `1.20.1`, `26.1` and suffixed forms like `1.20.5-pre1`
+    // or `1.20.1-R0.1-SNAPSHOT`; snapshot ids like `24w14a` are rejected.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let release = s.split(['-', ' ', '_']).next().unwrap_or(s);
+        let mut parts = release.split('.').map(str::parse::<u32>);
+        match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some(Ok(major)), Some(Ok(minor)), patch, None) => {
+                let patch = match patch {
+                    Some(Ok(patch)) => patch,
+                    None => 0,
+                    Some(Err(_)) => return Err(format!("invalid Minecraft version: {}", s)),
+                };
+                Ok(MinecraftVersion::new(major, minor, patch))
+            }
+            _ => Err(format!("invalid Minecraft version: {}", s)),
+        }
+    }
+}
+
+impl Display for MinecraftVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.patch == 0 {
+            write!(f, "{}.{}", self.major, self.minor)
+        } else {
+            write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+        }
+    }
+}